@@ -19,7 +19,7 @@ fn main() {
                 ];
 
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    vis_canvas(ui, Id::new("canvas"), &contents).unwrap();
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
                 });
             },
         ))
@@ -39,7 +39,7 @@ fn main() {
                 ];
 
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    vis_canvas(ui, Id::new("canvas"), &contents).unwrap();
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
                 });
             },
         ))
@@ -56,7 +56,7 @@ fn main() {
                     ];
 
                     egui::CentralPanel::default().show(ctx, |ui| {
-                        vis_canvas(ui, Id::new("canvas"), &contents).unwrap();
+                        VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
                     });
                 },
             )
@@ -80,7 +80,7 @@ fn main() {
                     ];
 
                     egui::CentralPanel::default().show(ctx, |ui| {
-                        vis_canvas(ui, Id::new("canvas"), &contents).unwrap();
+                        VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
                     });
                 },
             )
@@ -113,12 +113,618 @@ fn main() {
                     ];
 
                     egui::CentralPanel::default().show(ctx, |ui| {
-                        vis_canvas(ui, Id::new("canvas"), &contents).unwrap();
+                        VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
                     });
                 },
             )
                 .add_asset_file("./assets/logo.png".into()),
         )
+        .add_story(Story::new(
+            "raw_image_canvas",
+            story_body! {
+                use egui::Id;
+                use egui_viscanvas::colormap::Colormap;
+                use egui_viscanvas::*;
+
+                let data: Vec<u16> = (0..256 * 256).map(|i| ((i % 256) * (i / 256)) as u16).collect();
+                let colormap_id = egui::Id::new("raw_image_colormap");
+                let mut colormap = ctx.data(|d| d.get_temp::<Colormap>(colormap_id)).unwrap_or(Colormap::Jet);
+                let contents = vec![
+                    RawImage::from_u16("gradient", [256, 256], data)
+                        .with_auto_window_level()
+                        .with_colormap(colormap)
+                        .into(),
+                ];
+
+                egui::TopBottomPanel::top("colormap").show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        for candidate in Colormap::ALL {
+                            ui.selectable_value(&mut colormap, candidate, candidate.name());
+                        }
+                    });
+                });
+                ctx.data_mut(|d| d.insert_temp(colormap_id, colormap));
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas"))
+                        .max_zoom(64.0)
+                        .pan_bounds(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::splat(256.0)))
+                        .show(ui, &contents)
+                        .unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "multi_channel_image_canvas",
+            story_body! {
+                use egui::{Color32, Id};
+                use egui_viscanvas::*;
+
+                let horizontal: Vec<f32> = (0..128 * 128).map(|i| (i % 128) as f32 / 127.0).collect();
+                let vertical: Vec<f32> = (0..128 * 128).map(|i| (i / 128) as f32 / 127.0).collect();
+                let contents = vec![
+                    MultiChannelImage::new("channels", [128, 128])
+                        .with_channel(Channel::new(horizontal, Color32::from_rgb(255, 0, 255)))
+                        .with_channel(Channel::new(vertical, Color32::GREEN).with_gain(0.8))
+                        .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "text_box_canvas",
+            story_body! {
+                use egui::Id;
+                use egui_viscanvas::*;
+
+                let contents = vec![
+                    Rectangle::new()
+                        .with_position(egui::Pos2::new(0.0, 0.0))
+                        .with_size(egui::Vec2::new(100.0, 100.0))
+                        .with_stroke_color(egui::Color32::from_rgb(255, 0, 0))
+                        .with_stroke_thickness(2.0).into(),
+                    TextBox::new(
+                        egui::Pos2::new(110.0, 0.0),
+                        160.0,
+                        "Sticky notes wrap their text inside the box and grow downwards as needed.",
+                    )
+                    .into(),
+                    Callout::new(egui::Pos2::new(100.0, 100.0), "Corner of the box")
+                        .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "badge_canvas",
+            story_body! {
+                use egui::{Align2, Color32, Id, Pos2, Vec2};
+                use egui_viscanvas::*;
+
+                let contents = vec![
+                    Rectangle::new()
+                        .with_position(Pos2::new(0.0, 0.0))
+                        .with_size(Vec2::new(100.0, 100.0))
+                        .with_stroke_color(Color32::from_rgb(0, 160, 0))
+                        .with_stroke_thickness(2.0).into(),
+                    Badge::new(Pos2::new(100.0, 0.0), "OK")
+                        .with_fill_color(Color32::from_rgb(0, 160, 0))
+                        .with_align(Align2::LEFT_BOTTOM)
+                        .into(),
+                    Rectangle::new()
+                        .with_position(Pos2::new(150.0, 0.0))
+                        .with_size(Vec2::new(100.0, 100.0))
+                        .with_stroke_color(Color32::from_rgb(220, 140, 0))
+                        .with_stroke_thickness(2.0).into(),
+                    Badge::new(Pos2::new(250.0, 0.0), "⚠ #12")
+                        .with_fill_color(Color32::from_rgb(220, 140, 0))
+                        .with_align(Align2::LEFT_BOTTOM)
+                        .with_pulse(1.5)
+                        .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "gauge_canvas",
+            story_body! {
+                use egui::{Id, Pos2, Vec2};
+                use egui_viscanvas::*;
+
+                let contents = vec![
+                    Circle::new()
+                        .with_center(Pos2::new(50.0, 50.0))
+                        .with_radius(20.0)
+                        .with_stroke_thickness(2.0).into(),
+                    Gauge::new(Pos2::new(50.0, 50.0), 0.8)
+                        .with_offset(Vec2::new(0.0, -30.0))
+                        .into(),
+                    Circle::new()
+                        .with_center(Pos2::new(150.0, 50.0))
+                        .with_radius(20.0)
+                        .with_stroke_thickness(2.0).into(),
+                    Gauge::new(Pos2::new(150.0, 50.0), 0.25)
+                        .with_style(GaugeStyle::Radial)
+                        .with_size(Vec2::splat(24.0))
+                        .with_offset(Vec2::new(0.0, -36.0))
+                        .into(),
+                    MiniTable::new(Pos2::new(170.0, 50.0))
+                        .with_row("id", 7)
+                        .with_row("battery", "25%")
+                        .with_row("status", "returning to dock")
+                        .into(),
+                    Sparkline::new(Pos2::new(70.0, 50.0), vec![0.2, 0.4, 0.35, 0.6, 0.8, 0.75, 0.9])
+                        .with_range(0.0, 1.0)
+                        .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "custom_item_canvas",
+            story_body! {
+                use egui::{Color32, Id, Painter, Pos2, Response, Stroke, Ui, Vec2};
+                use egui_viscanvas::error::VisCanvasResult;
+                use egui_viscanvas::*;
+
+                #[derive(Debug, Clone)]
+                struct Crosshair {
+                    center: Pos2,
+                    size: f32,
+                }
+
+                impl CanvasItem for Crosshair {
+                    fn show(
+                        &self,
+                        _ui: &mut Ui,
+                        painter: &mut Painter,
+                        canvas_state: &VisCanvasStateInner,
+                    ) -> VisCanvasResult<Option<Response>> {
+                        let stroke = Stroke::new(2.0, Color32::RED);
+                        for direction in [Vec2::X, Vec2::Y] {
+                            painter.line_segment(
+                                [
+                                    canvas_state.to_screen_pos(painter, self.center - direction * self.size),
+                                    canvas_state.to_screen_pos(painter, self.center + direction * self.size),
+                                ],
+                                stroke,
+                            );
+                        }
+                        Ok(None)
+                    }
+                }
+
+                let contents = vec![Content::custom(Crosshair {
+                    center: Pos2::new(100.0, 100.0),
+                    size: 20.0,
+                })];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "polygon_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2};
+                use egui_viscanvas::*;
+
+                let contents = vec![
+                    Polygon::new(vec![
+                        Pos2::new(0.0, 0.0),
+                        Pos2::new(200.0, 0.0),
+                        Pos2::new(200.0, 150.0),
+                        Pos2::new(100.0, 60.0),
+                        Pos2::new(0.0, 150.0),
+                    ])
+                    .with_fill_color(Color32::from_rgba_unmultiplied(0, 120, 255, 80))
+                    .with_stroke_color(Color32::from_rgb(0, 120, 255))
+                    .with_stroke_thickness(2.0)
+                    .with_label("region of interest")
+                    .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "ellipse_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2, Vec2};
+                use egui_viscanvas::*;
+
+                let contents = vec![
+                    Ellipse::new(Pos2::new(100.0, 100.0), Vec2::new(80.0, 30.0))
+                        .with_rotation(0.5)
+                        .with_stroke_color(Color32::from_rgb(200, 0, 200))
+                        .with_stroke_thickness(2.0)
+                        .into(),
+                    Ellipse::from_covariance(Pos2::new(250.0, 100.0), [[400.0, 150.0], [150.0, 200.0]], 2.0)
+                        .with_fill_color(Color32::from_rgba_unmultiplied(0, 160, 0, 60))
+                        .with_stroke_color(Color32::from_rgb(0, 160, 0))
+                        .with_label("2σ")
+                        .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "bezier_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2};
+                use egui_viscanvas::*;
+
+                let contents = vec![
+                    CubicBezier::new(
+                        Pos2::new(50.0, 50.0),
+                        Pos2::new(150.0, 50.0),
+                        Pos2::new(100.0, 200.0),
+                        Pos2::new(250.0, 200.0),
+                    )
+                    .with_stroke_color(Color32::from_rgb(0, 100, 200))
+                    .with_stroke_thickness(2.0)
+                    .into(),
+                    QuadraticBezier::new(
+                        Pos2::new(50.0, 250.0),
+                        Pos2::new(150.0, 150.0),
+                        Pos2::new(250.0, 250.0),
+                    )
+                    .with_stroke_color(Color32::from_rgb(200, 100, 0))
+                    .with_stroke_thickness(2.0)
+                    .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "arc_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2};
+                use egui_viscanvas::*;
+                use std::f32::consts::FRAC_PI_4;
+
+                let contents = vec![
+                    Arc::new(Pos2::new(150.0, 150.0), 120.0, -FRAC_PI_4 * 3.0, -FRAC_PI_4)
+                        .with_pie()
+                        .with_fill_color(Color32::from_rgba_unmultiplied(0, 160, 0, 60))
+                        .with_stroke_color(Color32::from_rgb(0, 160, 0))
+                        .into(),
+                    Arc::new(Pos2::new(150.0, 150.0), 60.0, 0.0, FRAC_PI_4 * 5.0)
+                        .with_stroke_color(Color32::from_rgb(0, 0, 200))
+                        .with_stroke_thickness(2.0)
+                        .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "points_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2};
+                use egui_viscanvas::*;
+
+                let grid = |offset: f32| {
+                    (0..10_000)
+                        .map(|i| Pos2::new((i % 100) as f32 * 3.0 + offset, (i / 100) as f32 * 3.0))
+                        .collect::<Vec<_>>()
+                };
+                let contents = vec![
+                    Points::new(grid(0.0)).with_color(Color32::RED).into(),
+                    Points::new(grid(320.0))
+                        .with_marker(Marker::Cross)
+                        .with_size(3.0)
+                        .with_color(Color32::BLUE)
+                        .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "pick_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2, Vec2};
+                use egui_viscanvas::*;
+
+                let contents: Vec<Content> = (0..5)
+                    .map(|i| {
+                        Rectangle::new()
+                            .with_position(Pos2::new(i as f32 * 60.0, i as f32 * 30.0))
+                            .with_size(Vec2::new(80.0, 80.0))
+                            .with_fill_color(Color32::from_gray(100 + i * 30))
+                            .with_id(Id::new(("box", i)))
+                            .with_label(format!("box {i}"))
+                            .into()
+                    })
+                    .collect();
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let output = VisCanvas::new(Id::new("canvas"))
+                        .selection(egui::Stroke::new(2.0, Color32::GOLD))
+                        .show(ui, &contents)
+                        .unwrap();
+                    let hovered = output
+                        .content_responses
+                        .iter()
+                        .find(|content_response| content_response.hovered)
+                        .and_then(|hovered| contents.iter().find(|c| c.id() == Some(hovered.id)));
+                    if let Some(label) = hovered.and_then(Content::label) {
+                        output.response.on_hover_text(label);
+                    }
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "camera_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2, Rect, Vec2};
+                use egui_viscanvas::*;
+
+                let boxes: Vec<Rect> = (0..50)
+                    .map(|i| {
+                        let pos = Pos2::new((i % 10) as f32 * 150.0, (i / 10) as f32 * 150.0);
+                        Rect::from_min_size(pos, Vec2::splat(100.0))
+                    })
+                    .collect();
+                let contents: Vec<Content> = boxes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, rect)| {
+                        Rectangle::new()
+                            .with_position(rect.min)
+                            .with_size(rect.size())
+                            .with_stroke_color(Color32::DARK_BLUE)
+                            .with_label(format!("#{i}"))
+                            .into()
+                    })
+                    .collect();
+
+                let id = Id::new("canvas");
+                egui::TopBottomPanel::top("camera_controls").show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        let mut state = VisCanvasState::load(ui.ctx(), id);
+                        if ui.button("Center on #42").clicked() {
+                            state.center_on(boxes[42].center());
+                        }
+                        if ui.button("Zoom to #42").clicked() {
+                            state.zoom_to_rect(boxes[42].expand(20.0));
+                        }
+                        if ui.button("Fit").clicked() {
+                            state.fit_contents(&contents, 10.0);
+                        }
+                        if ui.button("Reset").clicked() {
+                            state.set_scale(1.0);
+                            state.set_shift(Vec2::ZERO);
+                        }
+                        state.store(ui.ctx());
+                    });
+                });
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let interaction = CanvasInteraction::default()
+                        .with_pan_button(egui::PointerButton::Primary)
+                        .with_scroll(ScrollAction::Zoom)
+                        .with_keyboard(true)
+                        .with_double_click(DoubleClickAction::FitContents { padding: 10.0 })
+                        .with_kinetic_friction(4.0);
+                    let output = VisCanvas::new(id)
+                        .auto_fit(10.0)
+                        .interaction(interaction)
+                        .show(ui, &contents)
+                        .unwrap();
+                    let tag_pos = output.state.canvas_to_screen(boxes[42].center_bottom());
+                    egui::Area::new(Id::new("box_42_tag"))
+                        .fixed_pos(tag_pos)
+                        .show(ui.ctx(), |ui| {
+                            let _ = ui.button("#42 details");
+                        });
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "stroke_style_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2};
+                use egui_viscanvas::stroke::{LineCap, LineJoin};
+                use egui_viscanvas::*;
+
+                let zigzag = |y: f32| {
+                    (0..5)
+                        .map(|i| Pos2::new(40.0 + i as f32 * 60.0, y + (i % 2) as f32 * 50.0))
+                        .collect::<Vec<_>>()
+                };
+                let styles = [
+                    (LineJoin::Miter, LineCap::Butt),
+                    (LineJoin::Round, LineCap::Round),
+                    (LineJoin::Bevel, LineCap::Square),
+                ];
+                let mut contents: Vec<Content> = styles
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (join, cap))| {
+                        PiecewiseSegment::new(zigzag(40.0 + i as f32 * 100.0))
+                            .unwrap()
+                            .with_stroke_color(Color32::from_rgba_unmultiplied(0, 90, 200, 200))
+                            .with_stroke_thickness(16.0)
+                            .with_line_join(join)
+                            .with_line_cap(cap)
+                            .into()
+                    })
+                    .collect();
+                let trail: Vec<Pos2> = (0..40)
+                    .map(|i| {
+                        let t = i as f32 * 0.15;
+                        Pos2::new(400.0 + 80.0 * t.cos(), 150.0 + 80.0 * (2.0 * t).sin() / 2.0)
+                    })
+                    .collect();
+                contents.push(
+                    Segment::new(Pos2::new(40.0, 360.0), Pos2::new(280.0, 360.0))
+                        .with_stroke_thickness(6.0)
+                        .with_gradient(Color32::GREEN, Color32::RED)
+                        .with_end_accent(SegmentAccent::Arrow)
+                        .into(),
+                );
+                contents.push(
+                    PiecewiseSegment::new(trail)
+                        .unwrap()
+                        .with_stroke_color(Color32::from_rgb(220, 60, 0))
+                        .with_taper(1.0, 12.0)
+                        .with_colors(
+                            (0..40)
+                                .map(|i| Color32::from_rgb(60 + i * 4, 60, 220 - i * 4))
+                                .collect(),
+                        )
+                        .with_line_join(LineJoin::Round)
+                        .with_line_cap(LineCap::Round)
+                        .into(),
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "chevrons_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2};
+                use egui_viscanvas::*;
+
+                let route = vec![
+                    Pos2::new(40.0, 60.0),
+                    Pos2::new(240.0, 60.0),
+                    Pos2::new(240.0, 200.0),
+                    Pos2::new(420.0, 200.0),
+                ];
+                let contents: Vec<Content> = vec![
+                    PiecewiseSegment::new(route)
+                        .unwrap()
+                        .with_stroke_color(Color32::from_rgb(0, 100, 200))
+                        .with_stroke_thickness(4.0)
+                        .with_chevrons(
+                            Chevrons::new(Thickness::Absolute(40.0)).with_color(Color32::WHITE),
+                        )
+                        .into(),
+                    CubicBezier::new(
+                        Pos2::new(40.0, 300.0),
+                        Pos2::new(160.0, 220.0),
+                        Pos2::new(300.0, 400.0),
+                        Pos2::new(420.0, 300.0),
+                    )
+                    .with_stroke_color(Color32::from_rgb(200, 100, 0))
+                    .with_stroke_thickness(2.0)
+                    .with_chevrons(Chevrons::new(Thickness::Relative(30.0)).with_size(10.0))
+                    .into(),
+                ];
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "label_style_canvas",
+            story_body! {
+                use egui::{Color32, FontId, Id, Pos2, Stroke, Vec2};
+                use egui_viscanvas::*;
+
+                let contents: Vec<Content> = [Color32::WHITE, Color32::GRAY, Color32::BLACK]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, fill)| {
+                        Rectangle::new()
+                            .with_position(Pos2::new(40.0 + i as f32 * 140.0, 60.0))
+                            .with_size(Vec2::new(120.0, 120.0))
+                            .with_fill_color(fill)
+                            .with_label(format!("class {i}"))
+                            .into()
+                    })
+                    .collect();
+                let style = CanvasStyle::default()
+                    .with_label_font(FontId::proportional(16.0))
+                    .with_label_color(Color32::WHITE)
+                    .with_label_outline(Stroke::new(1.5, Color32::BLACK));
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    VisCanvas::new(Id::new("canvas"))
+                        .background(Color32::from_gray(200))
+                        .style(style)
+                        .show(ui, &contents)
+                        .unwrap();
+                });
+            },
+        ))
+        .add_story(Story::new(
+            "drag_canvas",
+            story_body! {
+                use egui::{Color32, Id, Pos2, Vec2};
+                use egui_viscanvas::*;
+
+                let nodes_id = Id::new("drag_nodes");
+                let mut nodes = ctx
+                    .data(|d| d.get_temp::<Vec<Pos2>>(nodes_id))
+                    .unwrap_or_else(|| vec![Pos2::new(40.0, 40.0), Pos2::new(260.0, 160.0)]);
+                let node_size = Vec2::new(120.0, 60.0);
+                let mut contents: Vec<Content> = nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &pos)| {
+                        Rectangle::new()
+                            .with_position(pos)
+                            .with_size(node_size)
+                            .with_fill_color(Color32::from_rgb(200, 220, 255))
+                            .with_stroke_color(Color32::from_rgb(0, 90, 200))
+                            .with_label(format!("node {i}"))
+                            .with_id(Id::new(("node", i)))
+                            .with_draggable()
+                            .into()
+                    })
+                    .collect();
+                contents.push(
+                    Segment::new(nodes[0] + node_size / 2.0, nodes[1] + node_size / 2.0)
+                        .with_stroke_thickness(2.0)
+                        .with_z_index(-1)
+                        .into(),
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let output = VisCanvas::new(Id::new("canvas")).show(ui, &contents).unwrap();
+                    if let Some(drag) = output.drag {
+                        for (i, node) in nodes.iter_mut().enumerate() {
+                            if drag.id == Id::new(("node", i)) {
+                                *node = drag.new_position;
+                            }
+                        }
+                    }
+                });
+                ctx.data_mut(|d| d.insert_temp(nodes_id, nodes));
+            },
+        ))
         .build();
 
     run_story_book("egui-viscanvas", story_book, NativeOptions::default()).unwrap();
@@ -0,0 +1,110 @@
+use egui::Color32;
+
+/// Maps normalized scalar values to colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Colormap {
+    #[default]
+    Gray,
+    Jet,
+    Hot,
+    Viridis,
+    Inferno,
+    Turbo,
+}
+
+// Polynomial fits of the matplotlib colormaps, coefficients from t^0 to t^6.
+const VIRIDIS: [[f64; 3]; 7] = [
+    [0.2777273272234177, 0.005407344544966578, 0.3340998053353061],
+    [0.1050930431085774, 1.404613529898575, 1.384590162594685],
+    [-0.3308618287255563, 0.214847559468213, 0.09509516302823659],
+    [-4.634230498983486, -5.799100973351585, -19.33244095627987],
+    [6.228269936347081, 14.17993336680509, 56.69055260068105],
+    [4.776384997670288, -13.74514537774601, -65.35303263337234],
+    [-5.435455855934631, 4.645852612178535, 26.3124352495832],
+];
+
+const INFERNO: [[f64; 3]; 7] = [
+    [
+        0.0002189403691192265,
+        0.001651004631001012,
+        -0.01948089843709184,
+    ],
+    [0.1065134194856116, 0.5639564367884091, 3.932712388889277],
+    [11.60249308247187, -3.972853965665698, -15.9423941062914],
+    [-41.70399613139459, 17.43639888205313, 44.35414519872813],
+    [77.162935699427, -33.40235894210092, -81.80730925738993],
+    [-71.31942824499214, 32.62606426397723, 73.20951985803202],
+    [25.13112622477341, -12.24266895238567, -23.07032500287172],
+];
+
+// Polynomial approximation of Google's Turbo, coefficients from t^0 to t^5.
+const TURBO: [[f64; 3]; 6] = [
+    [0.13572138, 0.09140261, 0.1066733],
+    [4.6153926, 2.19418839, 12.64194608],
+    [-42.66032258, 4.84296658, -60.58204836],
+    [132.13108234, -14.18503333, 110.36276771],
+    [-152.94239396, 4.27729857, -89.90310912],
+    [59.28637943, 2.82956604, 27.34824973],
+];
+
+fn polynomial(coefficients: &[[f64; 3]], t: f32) -> Color32 {
+    let t = t as f64;
+    let channel = |c: usize| {
+        let v = coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |acc, coefficient| acc * t + coefficient[c]);
+        (v.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+    Color32::from_rgb(channel(0), channel(1), channel(2))
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 6] = [
+        Colormap::Gray,
+        Colormap::Jet,
+        Colormap::Hot,
+        Colormap::Viridis,
+        Colormap::Inferno,
+        Colormap::Turbo,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Gray => "Gray",
+            Colormap::Jet => "Jet",
+            Colormap::Hot => "Hot",
+            Colormap::Viridis => "Viridis",
+            Colormap::Inferno => "Inferno",
+            Colormap::Turbo => "Turbo",
+        }
+    }
+
+    /// Maps `t` in `0.0..=1.0` to a color. Values outside the range are clamped.
+    pub fn map(&self, t: f32) -> Color32 {
+        let t = t.clamp(0.0, 1.0);
+        let ramp = |offset: f32| ((3.0 * t - offset).clamp(0.0, 1.0) * 255.0).round() as u8;
+        match self {
+            Colormap::Gray => {
+                let v = (t * 255.0).round() as u8;
+                Color32::from_gray(v)
+            }
+            Colormap::Jet => {
+                let channel = |offset: f32| {
+                    let v = (1.5 - (4.0 * t - offset).abs()).clamp(0.0, 1.0);
+                    (v * 255.0).round() as u8
+                };
+                Color32::from_rgb(channel(3.0), channel(2.0), channel(1.0))
+            }
+            Colormap::Hot => Color32::from_rgb(ramp(0.0), ramp(1.0), ramp(2.0)),
+            Colormap::Viridis => polynomial(&VIRIDIS, t),
+            Colormap::Inferno => polynomial(&INFERNO, t),
+            Colormap::Turbo => polynomial(&TURBO, t),
+        }
+    }
+
+    /// 256-entry lookup table, indexed by `(t * 255.0).round()`.
+    pub fn lut(&self) -> [Color32; 256] {
+        std::array::from_fn(|i| self.map(i as f32 / 255.0))
+    }
+}
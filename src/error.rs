@@ -5,6 +5,8 @@ use thiserror::Error;
 pub enum VisCanvasError {
     #[error("Load error: {0}")]
     LoadError(#[from] LoadError),
+    #[error("Invalid image data: expected {expected} samples, got {actual}")]
+    InvalidImageData { expected: usize, actual: usize },
 }
 
 pub type VisCanvasResult<T> = std::result::Result<T, VisCanvasError>;
@@ -1,19 +1,35 @@
+pub mod colormap;
 pub mod error;
+pub mod matching;
+pub mod morph;
+pub mod stroke;
+pub mod summary;
 
+use crate::colormap::Colormap;
 use crate::error::{Result, VisCanvasError};
-use egui::epaint::PathShape;
+use crate::stroke::{
+    polyline_mesh, vertex_stroke_mesh, LineCap, LineJoin, StrokeStyle, StrokeVertex,
+};
+use egui::emath::Rot2;
+use egui::epaint::{CubicBezierShape, PathShape, QuadraticBezierShape};
 use egui::load::TexturePoll;
 use egui::{
-    Align2, Color32, Context, Id, ImageSource, Painter, PointerButton, Pos2, Rect, Response,
-    Rounding, Sense, SizeHint, Stroke, TextureOptions, Ui, Vec2,
+    Align2, Color32, ColorImage, Context, EventFilter, Id, ImageSource, Key, Mesh, Painter,
+    PointerButton, Pos2, Rect, Response, Rounding, Sense, SizeHint, Stroke, TextureHandle,
+    TextureOptions, Ui, Vec2,
 };
-use egui::{FontId, Shape};
+use egui::{FontDefinitions, FontFamily, FontId, Galley, Shape};
 use num::Zero;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use std::sync;
 
 const SCROLL_SPEED: f32 = 1.0;
 const ZOOM_SPEED: f32 = 1.0;
+/// Pick radius around the pointer in screen pixels
+const HIT_TOLERANCE: f32 = 4.0;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Origin {
     TopLeft,
     BottomLeft,
@@ -27,13 +43,483 @@ pub enum Thickness {
     Absolute(f32),
 }
 
+/// Extension point for contents defined outside this crate. Implementors are
+/// painted inside the canvas pan/zoom transform like built-in contents; use
+/// [`VisCanvasStateInner::to_screen_pos`] to place geometry.
+pub trait CanvasItem: CanvasItemClone + std::fmt::Debug {
+    fn show(
+        &self,
+        ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>>;
+
+    /// Bounding box in canvas coordinates, if known.
+    fn bounding_rect(&self) -> Option<Rect> {
+        None
+    }
+
+    fn hit_test(&self, _pos: Pos2, _tolerance: f32) -> bool {
+        false
+    }
+
+    fn id(&self) -> Option<Id> {
+        None
+    }
+
+    fn draggable(&self) -> bool {
+        false
+    }
+
+    fn label(&self) -> Option<&str> {
+        None
+    }
+
+    fn z_index(&self) -> i32 {
+        0
+    }
+
+    fn zoom_range(&self) -> Option<&RangeInclusive<f32>> {
+        None
+    }
+}
+
+/// Lets `Box<dyn CanvasItem>` be cloned. Implemented for every `Clone` item.
+pub trait CanvasItemClone {
+    fn clone_box(&self) -> Box<dyn CanvasItem>;
+}
+
+impl<T: CanvasItem + Clone + 'static> CanvasItemClone for T {
+    fn clone_box(&self) -> Box<dyn CanvasItem> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CanvasItem> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Time-driven style modifier for alert states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StyleAnimation {
+    /// Opacity oscillates smoothly between 0.2 and 1.0 once per `period` seconds
+    Pulse { period: f32 },
+    /// Fully visible for the first half of each `period` seconds, hidden for the second
+    Blink { period: f32 },
+}
+
+impl StyleAnimation {
+    pub fn opacity(&self, time: f64) -> f32 {
+        match *self {
+            StyleAnimation::Pulse { period } => {
+                let phase = (time / period.max(f32::EPSILON) as f64).fract() as f32;
+                0.6 + 0.4 * (phase * std::f32::consts::TAU).cos()
+            }
+            StyleAnimation::Blink { period } => {
+                if (time / period.max(f32::EPSILON) as f64).fract() < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Content {
     Image(Image),
+    RawImage(RawImage),
+    MultiChannelImage(MultiChannelImage),
+    DepthImage(DepthImage),
     Rectangle(Rectangle),
     Circle(Circle),
     Segment(Segment),
     PiecewiseSegment(PiecewiseSegment),
+    TextBox(TextBox),
+    Callout(Callout),
+    Badge(Badge),
+    Gauge(Gauge),
+    MiniTable(MiniTable),
+    Sparkline(Sparkline),
+    Custom(Box<dyn CanvasItem>),
+    Polygon(Polygon),
+    Ellipse(Ellipse),
+    CubicBezier(CubicBezier),
+    QuadraticBezier(QuadraticBezier),
+    Arc(Arc),
+    Points(Points),
+}
+
+impl Content {
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Content::Image(_) => "Image",
+            Content::RawImage(_) => "RawImage",
+            Content::MultiChannelImage(_) => "MultiChannelImage",
+            Content::DepthImage(_) => "DepthImage",
+            Content::Rectangle(_) => "Rectangle",
+            Content::Circle(_) => "Circle",
+            Content::Segment(_) => "Segment",
+            Content::PiecewiseSegment(_) => "PiecewiseSegment",
+            Content::TextBox(_) => "TextBox",
+            Content::Callout(_) => "Callout",
+            Content::Badge(_) => "Badge",
+            Content::Gauge(_) => "Gauge",
+            Content::MiniTable(_) => "MiniTable",
+            Content::Sparkline(_) => "Sparkline",
+            Content::Custom(_) => "Custom",
+            Content::Polygon(_) => "Polygon",
+            Content::Ellipse(_) => "Ellipse",
+            Content::CubicBezier(_) => "CubicBezier",
+            Content::QuadraticBezier(_) => "QuadraticBezier",
+            Content::Arc(_) => "Arc",
+            Content::Points(_) => "Points",
+        }
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            Content::Rectangle(rect) => rect.label.as_deref(),
+            Content::Circle(circle) => circle.label.as_deref(),
+            Content::TextBox(text_box) => Some(&text_box.text),
+            Content::Callout(callout) => Some(&callout.text),
+            Content::Badge(badge) => Some(&badge.text),
+            Content::Custom(item) => item.label(),
+            Content::Polygon(polygon) => polygon.label.as_deref(),
+            Content::Ellipse(ellipse) => ellipse.label.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn id(&self) -> Option<Id> {
+        match self {
+            Content::Image(image) => image.id,
+            Content::RawImage(raw_image) => raw_image.id,
+            Content::MultiChannelImage(image) => image.id,
+            Content::DepthImage(depth_image) => depth_image.image.id,
+            Content::Rectangle(rect) => rect.id,
+            Content::Circle(circle) => circle.id,
+            Content::Segment(segment) => segment.id,
+            Content::PiecewiseSegment(piecewise_segment) => piecewise_segment.id,
+            Content::TextBox(text_box) => text_box.id,
+            Content::Callout(callout) => callout.id,
+            Content::Badge(badge) => badge.id,
+            Content::Gauge(gauge) => gauge.id,
+            Content::MiniTable(mini_table) => mini_table.id,
+            Content::Sparkline(sparkline) => sparkline.id,
+            Content::Custom(item) => item.id(),
+            Content::Polygon(polygon) => polygon.id,
+            Content::Ellipse(ellipse) => ellipse.id,
+            Content::CubicBezier(bezier) => bezier.id,
+            Content::QuadraticBezier(bezier) => bezier.id,
+            Content::Arc(arc) => arc.id,
+            Content::Points(points) => points.id,
+        }
+    }
+
+    /// Whether the canvas moves this content on a primary-button drag and reports
+    /// it in [`VisCanvasOutput::drag`]. Requires an id.
+    pub fn is_draggable(&self) -> bool {
+        match self {
+            Content::Image(_) => false,
+            Content::RawImage(_) => false,
+            Content::MultiChannelImage(_) => false,
+            Content::DepthImage(_) => false,
+            Content::Rectangle(rect) => rect.draggable,
+            Content::Circle(circle) => circle.draggable,
+            Content::Segment(segment) => segment.draggable,
+            Content::PiecewiseSegment(piecewise_segment) => piecewise_segment.draggable,
+            Content::TextBox(text_box) => text_box.draggable,
+            Content::Callout(callout) => callout.draggable,
+            Content::Badge(badge) => badge.draggable,
+            Content::Gauge(gauge) => gauge.draggable,
+            Content::MiniTable(mini_table) => mini_table.draggable,
+            Content::Sparkline(sparkline) => sparkline.draggable,
+            Content::Custom(item) => item.draggable(),
+            Content::Polygon(polygon) => polygon.draggable,
+            Content::Ellipse(ellipse) => ellipse.draggable,
+            Content::CubicBezier(bezier) => bezier.draggable,
+            Content::QuadraticBezier(bezier) => bezier.draggable,
+            Content::Arc(arc) => arc.draggable,
+            Content::Points(points) => points.draggable,
+        }
+    }
+
+    pub fn z_index(&self) -> i32 {
+        match self {
+            Content::Image(image) => image.z_index,
+            Content::RawImage(raw_image) => raw_image.z_index,
+            Content::MultiChannelImage(image) => image.z_index,
+            Content::DepthImage(depth_image) => depth_image.image.z_index,
+            Content::Rectangle(rect) => rect.z_index,
+            Content::Circle(circle) => circle.z_index,
+            Content::Segment(segment) => segment.z_index,
+            Content::PiecewiseSegment(piecewise_segment) => piecewise_segment.z_index,
+            Content::TextBox(text_box) => text_box.z_index,
+            Content::Callout(callout) => callout.z_index,
+            Content::Badge(badge) => badge.z_index,
+            Content::Gauge(gauge) => gauge.z_index,
+            Content::MiniTable(mini_table) => mini_table.z_index,
+            Content::Sparkline(sparkline) => sparkline.z_index,
+            Content::Custom(item) => item.z_index(),
+            Content::Polygon(polygon) => polygon.z_index,
+            Content::Ellipse(ellipse) => ellipse.z_index,
+            Content::CubicBezier(bezier) => bezier.z_index,
+            Content::QuadraticBezier(bezier) => bezier.z_index,
+            Content::Arc(arc) => arc.z_index,
+            Content::Points(points) => points.z_index,
+        }
+    }
+
+    pub fn zoom_range(&self) -> Option<&RangeInclusive<f32>> {
+        match self {
+            Content::Image(image) => image.zoom_range.as_ref(),
+            Content::RawImage(raw_image) => raw_image.zoom_range.as_ref(),
+            Content::MultiChannelImage(image) => image.zoom_range.as_ref(),
+            Content::DepthImage(depth_image) => depth_image.image.zoom_range.as_ref(),
+            Content::Rectangle(rect) => rect.zoom_range.as_ref(),
+            Content::Circle(circle) => circle.zoom_range.as_ref(),
+            Content::Segment(segment) => segment.zoom_range.as_ref(),
+            Content::PiecewiseSegment(piecewise_segment) => piecewise_segment.zoom_range.as_ref(),
+            Content::TextBox(text_box) => text_box.zoom_range.as_ref(),
+            Content::Callout(callout) => callout.zoom_range.as_ref(),
+            Content::Badge(badge) => badge.zoom_range.as_ref(),
+            Content::Gauge(gauge) => gauge.zoom_range.as_ref(),
+            Content::MiniTable(mini_table) => mini_table.zoom_range.as_ref(),
+            Content::Sparkline(sparkline) => sparkline.zoom_range.as_ref(),
+            Content::Custom(item) => item.zoom_range(),
+            Content::Polygon(polygon) => polygon.zoom_range.as_ref(),
+            Content::Ellipse(ellipse) => ellipse.zoom_range.as_ref(),
+            Content::CubicBezier(bezier) => bezier.zoom_range.as_ref(),
+            Content::QuadraticBezier(bezier) => bezier.zoom_range.as_ref(),
+            Content::Arc(arc) => arc.zoom_range.as_ref(),
+            Content::Points(points) => points.zoom_range.as_ref(),
+        }
+    }
+
+    pub fn animation(&self) -> Option<StyleAnimation> {
+        match self {
+            Content::Rectangle(rect) => rect.animation,
+            Content::Circle(circle) => circle.animation,
+            Content::Segment(segment) => segment.animation,
+            Content::PiecewiseSegment(piecewise_segment) => piecewise_segment.animation,
+            Content::Badge(badge) => badge.animation,
+            Content::Polygon(polygon) => polygon.animation,
+            Content::Ellipse(ellipse) => ellipse.animation,
+            Content::CubicBezier(bezier) => bezier.animation,
+            Content::QuadraticBezier(bezier) => bezier.animation,
+            Content::Arc(arc) => arc.animation,
+            Content::Points(points) => points.animation,
+            _ => None,
+        }
+    }
+
+    pub fn is_visible_at_scale(&self, scale: f32) -> bool {
+        match self.zoom_range() {
+            Some(zoom_range) => zoom_range.contains(&scale),
+            None => true,
+        }
+    }
+
+    /// Bounding box in canvas coordinates, or `None` when it depends on a loaded texture.
+    pub fn bounding_rect(&self) -> Option<Rect> {
+        match self {
+            Content::Image(_) => None,
+            Content::RawImage(raw_image) => Some(raw_image.bounding_rect()),
+            Content::MultiChannelImage(image) => Some(image.bounding_rect()),
+            Content::DepthImage(depth_image) => Some(depth_image.image.bounding_rect()),
+            Content::Rectangle(rect) => Some(rect.bounding_rect()),
+            Content::Circle(circle) => Some(circle.bounding_rect()),
+            Content::Segment(segment) => Some(segment.bounding_rect()),
+            Content::PiecewiseSegment(piecewise_segment) => Some(piecewise_segment.bounding_rect()),
+            Content::TextBox(text_box) => Some(text_box.bounding_rect()),
+            Content::Callout(callout) => Some(callout.bounding_rect()),
+            Content::Badge(badge) => Some(badge.bounding_rect()),
+            Content::Gauge(gauge) => Some(gauge.bounding_rect()),
+            Content::MiniTable(mini_table) => Some(mini_table.bounding_rect()),
+            Content::Sparkline(sparkline) => Some(sparkline.bounding_rect()),
+            Content::Custom(item) => item.bounding_rect(),
+            Content::Polygon(polygon) => Some(polygon.bounding_rect()),
+            Content::Ellipse(ellipse) => Some(ellipse.bounding_rect()),
+            Content::CubicBezier(bezier) => Some(bezier.bounding_rect()),
+            Content::QuadraticBezier(bezier) => Some(bezier.bounding_rect()),
+            Content::Arc(arc) => Some(arc.bounding_rect()),
+            Content::Points(points) => Some(points.bounding_rect()),
+        }
+    }
+
+    /// Screen rect laid out by `show` for contents whose extent depends on text
+    /// layout or stays constant on screen, or `None` if [`Self::bounding_rect`] is
+    /// exact. Screen-sized contents only report their anchor as bounding rect.
+    fn screen_rect(&self, painter: &Painter, canvas_state: &VisCanvasStateInner) -> Option<Rect> {
+        match self {
+            Content::TextBox(text_box) => Some(text_box.layout(painter, canvas_state).0),
+            Content::Callout(callout) => Some(callout.layout(painter, canvas_state).0),
+            Content::Badge(badge) => Some(badge.layout(painter, canvas_state).0),
+            Content::Gauge(gauge) => Some(gauge.layout(painter, canvas_state)),
+            Content::MiniTable(table) => table.layout(painter, canvas_state).map(|(rect, ..)| rect),
+            Content::Sparkline(sparkline) => Some(sparkline.layout(painter, canvas_state)),
+            _ => None,
+        }
+    }
+
+    /// Whether `pos` (in canvas coordinates) hits this content, allowing `tolerance`
+    /// canvas units of slack.
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        match self {
+            Content::Image(_) => false,
+            Content::RawImage(raw_image) => raw_image.hit_test(pos, tolerance),
+            Content::MultiChannelImage(image) => image.hit_test(pos, tolerance),
+            Content::DepthImage(depth_image) => depth_image.image.hit_test(pos, tolerance),
+            Content::Rectangle(rect) => rect.hit_test(pos, tolerance),
+            Content::Circle(circle) => circle.hit_test(pos, tolerance),
+            Content::Segment(segment) => segment.hit_test(pos, tolerance),
+            Content::PiecewiseSegment(piecewise_segment) => {
+                piecewise_segment.hit_test(pos, tolerance)
+            }
+            Content::TextBox(text_box) => text_box.hit_test(pos, tolerance),
+            Content::Callout(callout) => callout.hit_test(pos, tolerance),
+            Content::Badge(badge) => badge.hit_test(pos, tolerance),
+            Content::Gauge(gauge) => gauge.hit_test(pos, tolerance),
+            Content::MiniTable(mini_table) => mini_table.hit_test(pos, tolerance),
+            Content::Sparkline(sparkline) => sparkline.hit_test(pos, tolerance),
+            Content::Custom(item) => item.hit_test(pos, tolerance),
+            Content::Polygon(polygon) => polygon.hit_test(pos, tolerance),
+            Content::Ellipse(ellipse) => ellipse.hit_test(pos, tolerance),
+            Content::CubicBezier(bezier) => bezier.hit_test(pos, tolerance),
+            Content::QuadraticBezier(bezier) => bezier.hit_test(pos, tolerance),
+            Content::Arc(arc) => arc.hit_test(pos, tolerance),
+            Content::Points(points) => points.hit_test(pos, tolerance),
+        }
+    }
+}
+
+/// Union of the bounding boxes of all contents with a known extent.
+pub fn contents_bounding_rect(contents: &[Content]) -> Option<Rect> {
+    contents
+        .iter()
+        .filter_map(Content::bounding_rect)
+        .reduce(|a, b| a.union(b))
+}
+
+fn distance_to_segment(pos: Pos2, start: Pos2, end: Pos2) -> f32 {
+    let direction = end - start;
+    let length_sq = direction.length_sq();
+    if length_sq.is_zero() {
+        return pos.distance(start);
+    }
+    let t = ((pos - start).dot(direction) / length_sq).clamp(0.0, 1.0);
+    pos.distance(start + direction * t)
+}
+
+fn distance_to_polyline(pos: Pos2, points: &[Pos2]) -> f32 {
+    points
+        .windows(2)
+        .map(|w| distance_to_segment(pos, w[0], w[1]))
+        .fold(f32::INFINITY, f32::min)
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn point_in_triangle(p: Pos2, a: Pos2, b: Pos2, c: Pos2) -> bool {
+    let d1 = cross(p - a, b - a);
+    let d2 = cross(p - b, c - b);
+    let d3 = cross(p - c, a - c);
+    let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_negative && has_positive)
+}
+
+/// Even-odd point-in-polygon test.
+fn point_in_polygon(pos: Pos2, vertices: &[Pos2]) -> bool {
+    let mut inside = false;
+    let mut j = vertices.len().wrapping_sub(1);
+    for (i, vi) in vertices.iter().enumerate() {
+        let vj = vertices[j];
+        if (vi.y > pos.y) != (vj.y > pos.y)
+            && pos.x < (vj.x - vi.x) * (pos.y - vi.y) / (vj.y - vi.y) + vi.x
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Ear-clipping triangulation of a simple polygon, so that concave polygons
+/// can be filled (epaint only fills convex paths correctly). Returns vertex
+/// index triples.
+fn triangulate(vertices: &[Pos2]) -> Vec<[usize; 3]> {
+    let n = vertices.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    let signed_area: f32 = (0..n)
+        .map(|i| cross(vertices[i].to_vec2(), vertices[(i + 1) % n].to_vec2()))
+        .sum();
+    let mut remaining: Vec<usize> = if signed_area >= 0.0 {
+        (0..n).collect()
+    } else {
+        (0..n).rev().collect()
+    };
+
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (ia, ib, ic) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            let (a, b, c) = (vertices[ia], vertices[ib], vertices[ic]);
+            cross(b - a, c - b) > 0.0
+                && !remaining.iter().any(|&j| {
+                    j != ia && j != ib && j != ic && point_in_triangle(vertices[j], a, b, c)
+                })
+        });
+        let Some(i) = ear else {
+            // Self-intersecting or degenerate input: fan out whatever is left.
+            break;
+        };
+        triangles.push([
+            remaining[(i + m - 1) % m],
+            remaining[i],
+            remaining[(i + 1) % m],
+        ]);
+        remaining.remove(i);
+    }
+    for i in 1..remaining.len().saturating_sub(1) {
+        triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+    }
+    triangles
+}
+
+/// Iterates contents in paint order: ascending z-index, then insertion order.
+///
+/// The sort is stable, so contents sharing a z-index are painted in the order
+/// they appear in the slice.
+pub fn iter_ordered(contents: &[Content]) -> impl Iterator<Item = &Content> {
+    let mut ordered: Vec<&Content> = contents.iter().collect();
+    ordered.sort_by_key(|content| content.z_index());
+    ordered.into_iter()
+}
+
+impl Content {
+    pub fn custom(item: impl CanvasItem + 'static) -> Self {
+        Content::Custom(Box::new(item))
+    }
+}
+
+impl From<Box<dyn CanvasItem>> for Content {
+    fn from(item: Box<dyn CanvasItem>) -> Self {
+        Content::Custom(item)
+    }
 }
 
 impl From<Rectangle> for Content {
@@ -48,6 +534,16 @@ pub struct SegmentData {
     pub end: Pos2,
 }
 
+impl SegmentData {
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_two_pos(self.start, self.end)
+    }
+
+    pub fn distance(&self, pos: Pos2) -> f32 {
+        distance_to_segment(pos, self.start, self.end)
+    }
+}
+
 #[derive(Debug, Clone, Default, Copy)]
 pub enum SegmentAccent {
     #[default]
@@ -59,7 +555,16 @@ pub enum SegmentAccent {
 pub struct Segment {
     pub data: SegmentData,
     pub stroke: Stroke,
+    pub stroke_style: StrokeStyle,
+    /// Start and end colors blended along the segment, overriding `stroke.color`
+    pub gradient: Option<(Color32, Color32)>,
     pub accents: (SegmentAccent, SegmentAccent),
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
 }
 
 fn arrow_head_shape(
@@ -91,12 +596,127 @@ fn arrow_head_shape(
     )
 }
 
+/// Evenly spaced direction chevrons along a path, pointing from its start to its end.
+#[derive(Debug, Clone)]
+pub struct Chevrons {
+    /// Distance between chevrons along the path
+    pub spacing: Thickness,
+    /// Chevron length and width in screen pixels
+    pub size: f32,
+    /// Defaults to the path's stroke color
+    pub color: Option<Color32>,
+}
+
+impl Chevrons {
+    pub fn new(spacing: Thickness) -> Self {
+        Self {
+            spacing,
+            size: 8.0,
+            color: None,
+        }
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Chevron shapes along a polyline in screen coordinates. The first chevron
+    /// sits half a spacing from the start; chevrons outside `clip_rect` are skipped.
+    fn shapes(&self, points: &[Pos2], scale: f32, stroke: Stroke, clip_rect: Rect) -> Vec<Shape> {
+        let spacing = match self.spacing {
+            Thickness::Relative(spacing) => spacing * scale,
+            Thickness::Absolute(spacing) => spacing,
+        };
+        if spacing.is_nan() || spacing < 1.0 || self.size <= 0.0 {
+            return Vec::new();
+        }
+        let stroke = Stroke::new(stroke.width.max(1.0), self.color.unwrap_or(stroke.color));
+        let half = self.size / 2.0;
+        let clip_rect = clip_rect.expand(self.size);
+
+        let mut shapes = Vec::new();
+        // Distance from the start of the current segment to the next chevron
+        let mut remaining = spacing / 2.0;
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let length = start.distance(end);
+            if remaining > length {
+                remaining -= length;
+                continue;
+            }
+            let Some((enter, exit)) = clip_segment(start, end, clip_rect) else {
+                remaining = spacing - (length - remaining) % spacing;
+                continue;
+            };
+            // Skip whole spacings up to where the segment enters the clip rect.
+            let (enter, exit) = (enter * length, exit * length);
+            if remaining < enter {
+                remaining += ((enter - remaining) / spacing).ceil() * spacing;
+            }
+            let direction = (end - start) / length;
+            let normal = direction.rot90() * half;
+            while remaining <= exit {
+                let center = start + direction * remaining;
+                let back = center - direction * half;
+                shapes.push(Shape::line(
+                    vec![back + normal, center + direction * half, back - normal],
+                    stroke,
+                ));
+                remaining += spacing;
+            }
+            remaining = if remaining <= length {
+                spacing - (length - remaining) % spacing
+            } else {
+                remaining - length
+            };
+        }
+        shapes
+    }
+}
+
+/// Parameter range `t0..=t1` of the segment `start + (end - start) * t` that lies
+/// inside `rect` (Liang-Barsky), or `None` if it misses the rect.
+fn clip_segment(start: Pos2, end: Pos2, rect: Rect) -> Option<(f32, f32)> {
+    let delta = end - start;
+    let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+    for (p, q) in [
+        (-delta.x, start.x - rect.min.x),
+        (delta.x, rect.max.x - start.x),
+        (-delta.y, start.y - rect.min.y),
+        (delta.y, rect.max.y - start.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    (t0 <= t1).then_some((t0, t1))
+}
+
 impl Segment {
     pub fn new(start: Pos2, end: Pos2) -> Self {
         Self {
             data: SegmentData { start, end },
             stroke: Stroke::new(1.0, Color32::BLACK),
+            stroke_style: StrokeStyle::default(),
+            gradient: None,
             accents: (SegmentAccent::None, SegmentAccent::None),
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+            animation: None,
         }
     }
 
@@ -110,6 +730,16 @@ impl Segment {
         self
     }
 
+    pub fn with_gradient(mut self, start_color: Color32, end_color: Color32) -> Self {
+        self.gradient = Some((start_color, end_color));
+        self
+    }
+
+    pub fn with_line_cap(mut self, cap: LineCap) -> Self {
+        self.stroke_style.cap = cap;
+        self
+    }
+
     pub fn with_start_accent(mut self, accent: SegmentAccent) -> Self {
         self.accents.0 = accent;
         self
@@ -120,6 +750,46 @@ impl Segment {
         self
     }
 
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        self.data.bounding_rect()
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.data.distance(pos) <= tolerance
+    }
+
     pub fn show(
         &self,
         _ui: &mut Ui,
@@ -130,6 +800,9 @@ impl Segment {
             + (self.data.start.to_vec2() * canvas_state.current_scale_vec() + canvas_state.shift);
         let mut end = painter.clip_rect().min
             + (self.data.end.to_vec2() * canvas_state.current_scale_vec() + canvas_state.shift);
+        let (start_color, end_color) = self
+            .gradient
+            .unwrap_or((self.stroke.color, self.stroke.color));
 
         match self.accents.0 {
             SegmentAccent::Arrow => {
@@ -137,7 +810,7 @@ impl Segment {
                     start,
                     (end - start).normalized(),
                     self.stroke.width,
-                    self.stroke.color,
+                    start_color,
                 );
                 painter.add(shape);
                 start += arrow_offset;
@@ -151,7 +824,7 @@ impl Segment {
                     end,
                     (start - end).normalized(),
                     self.stroke.width,
-                    self.stroke.color,
+                    end_color,
                 );
                 painter.add(shape);
                 end += arrow_offset;
@@ -159,7 +832,20 @@ impl Segment {
             SegmentAccent::None => {}
         }
 
-        painter.line_segment([start, end], self.stroke);
+        if self.gradient.is_none() && self.stroke_style.is_default() {
+            painter.line_segment([start, end], self.stroke);
+        } else {
+            let vertex = |pos, color| StrokeVertex {
+                pos,
+                width: self.stroke.width,
+                color,
+            };
+            painter.add(Shape::mesh(vertex_stroke_mesh(
+                &[vertex(start, start_color), vertex(end, end_color)],
+                false,
+                self.stroke_style,
+            )));
+        }
 
         Ok(None)
     }
@@ -175,6 +861,20 @@ impl From<Segment> for Content {
 pub struct PiecewiseSegment {
     pub data: Vec<SegmentData>,
     pub stroke: Stroke,
+    pub stroke_style: StrokeStyle,
+    /// Per-point stroke widths in screen pixels, in the order of the points passed
+    /// to [`Self::new`]. Overrides `stroke.width`; missing entries fall back to it.
+    pub widths: Option<Vec<f32>>,
+    /// Per-point stroke colors, blended along each segment. Overrides `stroke.color`
+    /// in the same way as `widths`.
+    pub colors: Option<Vec<Color32>>,
+    pub chevrons: Option<Chevrons>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
 }
 
 impl PiecewiseSegment {
@@ -184,19 +884,71 @@ impl PiecewiseSegment {
         painter: &mut Painter,
         canvas_state: &VisCanvasStateInner,
     ) -> Result<Option<Response>> {
-        for segment_data in &self.data {
-            let start = painter.clip_rect().min
-                + (segment_data.start.to_vec2() * canvas_state.current_scale_vec()
-                    + canvas_state.shift);
-            let end = painter.clip_rect().min
-                + (segment_data.end.to_vec2() * canvas_state.current_scale_vec()
-                    + canvas_state.shift);
-
-            painter.line_segment([start, end], self.stroke);
+        for chain in self.chains() {
+            let vertices: Vec<StrokeVertex> = chain
+                .into_iter()
+                .map(|vertex| StrokeVertex {
+                    pos: canvas_state.to_screen_pos(painter, vertex.pos),
+                    ..vertex
+                })
+                .collect();
+            let chevrons = self.chevrons.as_ref().map(|chevrons| {
+                let points: Vec<Pos2> = vertices.iter().map(|vertex| vertex.pos).collect();
+                chevrons.shapes(
+                    &points,
+                    canvas_state.current_scale,
+                    self.stroke,
+                    painter.clip_rect(),
+                )
+            });
+            if self.widths.is_none() && self.colors.is_none() && self.stroke_style.is_default() {
+                let points = vertices.into_iter().map(|vertex| vertex.pos).collect();
+                painter.add(PathShape::line(points, self.stroke));
+            } else {
+                painter.add(Shape::mesh(vertex_stroke_mesh(
+                    &vertices,
+                    false,
+                    self.stroke_style,
+                )));
+            }
+            painter.extend(chevrons.unwrap_or_default());
         }
         Ok(None)
     }
 
+    /// Stroke width and color at point `i`, where segment `i` runs from point `i`
+    /// to `i + 1`.
+    fn vertex_at(&self, pos: Pos2, i: usize) -> StrokeVertex {
+        StrokeVertex {
+            pos,
+            width: self
+                .widths
+                .as_ref()
+                .and_then(|widths| widths.get(i).copied())
+                .unwrap_or(self.stroke.width),
+            color: self
+                .colors
+                .as_ref()
+                .and_then(|colors| colors.get(i).copied())
+                .unwrap_or(self.stroke.color),
+        }
+    }
+
+    /// Runs of connected segments in canvas coordinates, so that joins can be drawn.
+    fn chains(&self) -> Vec<Vec<StrokeVertex>> {
+        let mut chains: Vec<Vec<StrokeVertex>> = Vec::new();
+        for (i, segment_data) in self.data.iter().enumerate() {
+            let end = self.vertex_at(segment_data.end, i + 1);
+            match chains.last_mut() {
+                Some(chain) if chain.last().map(|v| v.pos) == Some(segment_data.start) => {
+                    chain.push(end);
+                }
+                _ => chains.push(vec![self.vertex_at(segment_data.start, i), end]),
+            }
+        }
+        chains
+    }
+
     pub fn new(points: Vec<Pos2>) -> Option<Self> {
         if points.len() < 2 {
             return None;
@@ -213,6 +965,15 @@ impl PiecewiseSegment {
         Some(Self {
             data,
             stroke: Stroke::new(1.0, Color32::BLACK),
+            stroke_style: StrokeStyle::default(),
+            widths: None,
+            colors: None,
+            chevrons: None,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+            animation: None,
         })
     }
 
@@ -225,6 +986,86 @@ impl PiecewiseSegment {
         self.stroke.width = thickness;
         self
     }
+
+    pub fn with_line_join(mut self, join: LineJoin) -> Self {
+        self.stroke_style.join = join;
+        self
+    }
+
+    pub fn with_line_cap(mut self, cap: LineCap) -> Self {
+        self.stroke_style.cap = cap;
+        self
+    }
+
+    pub fn with_widths(mut self, widths: Vec<f32>) -> Self {
+        self.widths = Some(widths);
+        self
+    }
+
+    pub fn with_colors(mut self, colors: Vec<Color32>) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Widths blended linearly from `start_width` at the first point to `end_width`
+    /// at the last.
+    pub fn with_taper(self, start_width: f32, end_width: f32) -> Self {
+        let last = self.data.len().max(1) as f32;
+        let widths = (0..=self.data.len())
+            .map(|i| start_width + (end_width - start_width) * i as f32 / last)
+            .collect();
+        self.with_widths(widths)
+    }
+
+    pub fn with_chevrons(mut self, chevrons: Chevrons) -> Self {
+        self.chevrons = Some(chevrons);
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        self.data
+            .iter()
+            .map(SegmentData::bounding_rect)
+            .fold(Rect::NOTHING, |a, b| a.union(b))
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.data
+            .iter()
+            .any(|segment_data| segment_data.distance(pos) <= tolerance)
+    }
 }
 
 impl From<PiecewiseSegment> for Content {
@@ -241,6 +1082,12 @@ pub struct Circle {
     pub stroke: Option<Stroke>,
     pub label: Option<String>,
     pub responsable: bool,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
 }
 
 impl Circle {
@@ -294,6 +1141,46 @@ impl Circle {
         self
     }
 
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_center_size(self.center, Vec2::splat(self.radius * 2.0))
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.center.distance(pos) <= self.radius + tolerance
+    }
+
     pub fn show(
         &self,
         _ui: &mut Ui,
@@ -315,22 +1202,12 @@ impl Circle {
             },
         );
         if let Some(label) = &self.label {
-            let text_rect = painter.text(
-                center,
-                Align2::CENTER_CENTER,
-                label.as_str(),
-                FontId::default(),
-                Color32::BLACK,
-            );
-            if let Some(fill_color) = self.fill_color {
-                painter.rect_filled(text_rect, 0.0, fill_color);
-            }
-            let _text_rect = painter.text(
+            canvas_state.style().paint_label(
+                painter,
                 center,
                 Align2::CENTER_CENTER,
-                label.as_str(),
-                FontId::default(),
-                Color32::BLACK,
+                label,
+                self.fill_color,
             );
         }
 
@@ -354,6 +1231,12 @@ pub struct Rectangle {
     pub stroke: Option<Stroke>,
     pub label: Option<String>,
     pub responsable: bool,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
 }
 
 impl Rectangle {
@@ -409,18 +1292,61 @@ impl Rectangle {
         self
     }
 
-    pub fn with_responsable(mut self, responsable: bool) -> Self {
-        self.responsable = responsable;
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
         self
     }
 
-    pub fn show(
-        &self,
-        ui: &mut Ui,
-        painter: &mut Painter,
-        canvas_state: &VisCanvasStateInner,
-    ) -> Result<Option<Response>> {
-        let rect = Rect::from_two_pos(
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    pub fn with_responsable(mut self, responsable: bool) -> Self {
+        self.responsable = responsable;
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_two_pos(
+            Pos2::new(self.x, self.y),
+            Pos2::new(self.x + self.width, self.y + self.height),
+        )
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.bounding_rect().expand(tolerance).contains(pos)
+    }
+
+    pub fn show(
+        &self,
+        ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let rect = Rect::from_two_pos(
             painter.clip_rect().min
                 + (Vec2::new(self.x, self.y) * canvas_state.current_scale_vec()
                     + canvas_state.shift),
@@ -441,22 +1367,12 @@ impl Rectangle {
             },
         );
         if let Some(label) = &self.label {
-            let text_rect = painter.text(
-                rect.left_top(),
-                Align2::LEFT_BOTTOM,
-                label.as_str(),
-                FontId::default(),
-                Color32::BLACK,
-            );
-            if let Some(fill_color) = self.fill_color {
-                painter.rect_filled(text_rect, 0.0, fill_color);
-            }
-            let _text_rect = painter.text(
+            canvas_state.style().paint_label(
+                painter,
                 rect.left_top(),
                 Align2::LEFT_BOTTOM,
-                label.as_str(),
-                FontId::default(),
-                Color32::BLACK,
+                label,
+                self.fill_color,
             );
         }
 
@@ -471,6 +1387,10 @@ impl Rectangle {
 #[derive(Debug, Clone)]
 pub struct Image {
     image_source: ImageSource<'static>,
+    pub id: Option<Id>,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
 }
 
 impl From<Image> for Content {
@@ -481,7 +1401,27 @@ impl From<Image> for Content {
 
 impl Image {
     pub fn new(image_source: ImageSource<'static>) -> Self {
-        Self { image_source }
+        Self {
+            image_source,
+            id: None,
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
     }
 
     pub fn show(
@@ -514,142 +1454,4128 @@ impl Image {
     }
 }
 
-pub fn vis_canvas(
-    ui: &mut Ui,
-    id: Id,
-    origin: Origin,
-    contents: &[Content],
-) -> Result<(Response, VisCanvasState)> {
-    let mut state = VisCanvasState::load(ui.ctx(), id, origin);
-    let response = state.show_body(ui, contents)?;
-    state.store(ui.ctx());
-    Ok((response, state))
+#[derive(Debug, Clone)]
+pub enum RawImageData {
+    U8(sync::Arc<[u8]>),
+    U16(sync::Arc<[u16]>),
+    F32(sync::Arc<[f32]>),
 }
 
-pub struct VisCanvasState {
-    pub id: Id,
-    inner_state: VisCanvasStateInner,
+impl RawImageData {
+    pub fn len(&self) -> usize {
+        match self {
+            RawImageData::U8(data) => data.len(),
+            RawImageData::U16(data) => data.len(),
+            RawImageData::F32(data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<f32> {
+        match self {
+            RawImageData::U8(data) => data.get(index).map(|&v| v as f32),
+            RawImageData::U16(data) => data.get(index).map(|&v| v as f32),
+            RawImageData::F32(data) => data.get(index).copied(),
+        }
+    }
+
+    /// Full type range for integer data, `0.0..=1.0` for float data.
+    pub fn default_window_level(&self) -> WindowLevel {
+        match self {
+            RawImageData::U8(_) => WindowLevel::from_min_max(0.0, u8::MAX as f32),
+            RawImageData::U16(_) => WindowLevel::from_min_max(0.0, u16::MAX as f32),
+            RawImageData::F32(_) => WindowLevel::from_min_max(0.0, 1.0),
+        }
+    }
+
+    /// Window/level spanning the finite minimum and maximum, if any.
+    pub fn auto_window_level(&self) -> Option<WindowLevel> {
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        for v in (0..self.len()).filter_map(|i| self.get(i)) {
+            if v.is_finite() {
+                min = min.min(v);
+                max = max.max(v);
+            }
+        }
+        (min <= max).then(|| WindowLevel::from_min_max(min, max))
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct VisCanvasStateInner {
-    origin: Origin,
-    current_scale: f32,
-    shift: Vec2,
+impl From<Vec<u8>> for RawImageData {
+    fn from(value: Vec<u8>) -> Self {
+        RawImageData::U8(value.into())
+    }
 }
 
-impl Default for VisCanvasStateInner {
-    fn default() -> Self {
+impl From<Vec<u16>> for RawImageData {
+    fn from(value: Vec<u16>) -> Self {
+        RawImageData::U16(value.into())
+    }
+}
+
+impl From<Vec<f32>> for RawImageData {
+    fn from(value: Vec<f32>) -> Self {
+        RawImageData::F32(value.into())
+    }
+}
+
+/// Returns the texture cached under `key`, re-uploading it in place when
+/// `settings` differ from the ones it was built with.
+fn cached_texture<S>(
+    ctx: &Context,
+    key: Id,
+    settings: S,
+    make_image: impl FnOnce() -> Result<ColorImage>,
+) -> Result<TextureHandle>
+where
+    S: PartialEq + Clone + Send + Sync + 'static,
+{
+    let cached = ctx.data(|data| data.get_temp::<(S, TextureHandle)>(key));
+    if let Some((cached_settings, texture)) = &cached {
+        if *cached_settings == settings {
+            return Ok(texture.clone());
+        }
+    }
+
+    let image = make_image()?;
+    let texture = match cached {
+        Some((_, mut texture)) => {
+            texture.set(image, TextureOptions::NEAREST);
+            texture
+        }
+        None => ctx.load_texture(
+            format!("viscanvas_texture_{key:?}"),
+            image,
+            TextureOptions::NEAREST,
+        ),
+    };
+    ctx.data_mut(|data| data.insert_temp(key, (settings, texture.clone())));
+    Ok(texture)
+}
+
+fn check_sample_count(size: [usize; 2], data: &RawImageData) -> Result<()> {
+    let expected = size[0] * size[1];
+    if data.len() != expected {
+        return Err(VisCanvasError::InvalidImageData {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Linear mapping of raw sample values onto `0.0..=1.0`: values in
+/// `level - window / 2 ..= level + window / 2` span the full colormap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowLevel {
+    pub window: f32,
+    pub level: f32,
+}
+
+impl WindowLevel {
+    pub fn new(window: f32, level: f32) -> Self {
+        Self { window, level }
+    }
+
+    pub fn from_min_max(min: f32, max: f32) -> Self {
         Self {
-            current_scale: 1.0,
-            shift: Vec2::ZERO,
-            origin: Origin::TopLeft,
+            window: max - min,
+            level: (min + max) / 2.0,
+        }
+    }
+
+    pub fn min(&self) -> f32 {
+        self.level - self.window / 2.0
+    }
+
+    pub fn max(&self) -> f32 {
+        self.level + self.window / 2.0
+    }
+
+    pub fn normalize(&self, value: f32) -> f32 {
+        if self.window <= 0.0 {
+            return if value < self.level { 0.0 } else { 1.0 };
         }
+        ((value - self.min()) / self.window).clamp(0.0, 1.0)
     }
 }
 
-impl VisCanvasState {
-    pub fn screen_to_canvas(&self, screen_pos: Pos2) -> Pos2 {
-        assert_ne!(self.inner_state.current_scale, f32::zero());
-        ((screen_pos - self.inner_state.shift).to_vec2() / self.inner_state.current_scale_vec())
-            .to_pos2()
+/// Single-channel image (8-bit grayscale, 16-bit or float), mapped through a
+/// window/level and a colormap before texture upload.
+#[derive(Debug, Clone)]
+pub struct RawImage {
+    /// Identifies the cached texture. Use a new key whenever `data` changes.
+    texture_id: Id,
+    pub id: Option<Id>,
+    /// Width and height in pixels
+    pub size: [usize; 2],
+    pub data: RawImageData,
+    /// Canvas position of the top-left pixel
+    pub position: Pos2,
+    pub window_level: WindowLevel,
+    pub colormap: Colormap,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl From<RawImage> for Content {
+    fn from(value: RawImage) -> Self {
+        Content::RawImage(value)
     }
+}
 
-    pub(crate) fn load(ctx: &Context, id: Id, origin: Origin) -> Self {
-        let inner_state = ctx.data_mut(|data| {
-            let mut inner = data
-                .get_persisted::<VisCanvasStateInner>(id)
-                .unwrap_or_default();
-            inner.origin = origin;
-            inner
-        });
-        Self { id, inner_state }
+impl RawImage {
+    pub fn from_u8(
+        texture_key: impl Hash,
+        size: [usize; 2],
+        data: impl Into<sync::Arc<[u8]>>,
+    ) -> Self {
+        Self::new(texture_key, size, RawImageData::U8(data.into()))
     }
 
-    pub(crate) fn store(&self, ctx: &Context) {
-        ctx.data_mut(|data| {
-            data.insert_persisted(self.id, self.inner_state.clone());
-        });
+    pub fn from_u16(
+        texture_key: impl Hash,
+        size: [usize; 2],
+        data: impl Into<sync::Arc<[u16]>>,
+    ) -> Self {
+        Self::new(texture_key, size, RawImageData::U16(data.into()))
     }
 
-    pub(crate) fn show_body(&mut self, ui: &mut Ui, contents: &[Content]) -> Result<Response> {
-        let old_state = self.inner_state.clone();
+    pub fn from_f32(
+        texture_key: impl Hash,
+        size: [usize; 2],
+        data: impl Into<sync::Arc<[f32]>>,
+    ) -> Self {
+        Self::new(texture_key, size, RawImageData::F32(data.into()))
+    }
 
-        let response = ui
-            .centered_and_justified(|ui| {
-                let (response, mut painter) =
-                    ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
-                for content in contents {
-                    match content {
-                        Content::Rectangle(rect) => {
-                            rect.show(ui, &mut painter, &self.inner_state)?;
-                        }
-                        Content::Image(image) => {
-                            image.show(ui, &mut painter, &self.inner_state)?;
-                        }
-                        Content::Segment(segment) => {
-                            segment.show(ui, &mut painter, &self.inner_state)?;
-                        }
-                        Content::PiecewiseSegment(piecewise_segment) => {
-                            piecewise_segment.show(ui, &mut painter, &self.inner_state)?;
-                        }
-                        Content::Circle(circle) => {
-                            circle.show(ui, &mut painter, &self.inner_state)?;
-                        }
-                    }
-                }
-                Ok::<Response, VisCanvasError>(response)
-            })
-            .inner?;
+    /// `texture_key` identifies the cached texture; it does not make the image
+    /// pickable, use [`Self::with_id`] for that.
+    pub fn new(texture_key: impl Hash, size: [usize; 2], data: RawImageData) -> Self {
+        Self {
+            texture_id: Id::new(texture_key),
+            id: None,
+            size,
+            window_level: data.default_window_level(),
+            data,
+            position: Pos2::ZERO,
+            colormap: Colormap::default(),
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
 
-        let state = &mut self.inner_state;
-        if response.dragged_by(PointerButton::Middle) {
-            state.shift += response.drag_delta();
+    pub fn with_position(mut self, position: Pos2) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_window_level(mut self, window_level: WindowLevel) -> Self {
+        self.window_level = window_level;
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets the window/level to span the finite minimum and maximum of the data.
+    pub fn with_auto_window_level(mut self) -> Self {
+        if let Some(window_level) = self.data.auto_window_level() {
+            self.window_level = window_level;
         }
+        self
+    }
 
-        if let Some(hover_pos) = response.hover_pos() {
-            let hover_pos = hover_pos - response.rect.min;
-            ui.input(|input| {
-                // スクロール関係
-                {
-                    let dy = input.raw_scroll_delta.y;
-                    let dx = input.raw_scroll_delta.x;
-                    state.shift += egui::vec2(dx, dy) * SCROLL_SPEED;
-                }
-                // ズーム関係
-                {
-                    // https://chat.openai.com/share/e/c46c2795-a9e4-4f23-b04c-fa0b0e8ab818
-                    let scale = input.zoom_delta() * ZOOM_SPEED;
-                    let pos = hover_pos;
-                    state.current_scale *= scale;
-                    state.shift = state.shift * scale
-                        + egui::vec2(-scale * pos.x + pos.x, -scale * pos.y + pos.y);
-                }
-            });
+    pub fn with_colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = colormap;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_min_size(
+            self.position,
+            Vec2::new(self.size[0] as f32, self.size[1] as f32),
+        )
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.bounding_rect().expand(tolerance).contains(pos)
+    }
+
+    /// Raw sample value under a canvas position.
+    pub fn value_at(&self, pos: Pos2) -> Option<f32> {
+        let local = pos - self.position;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let (x, y) = (local.x as usize, local.y as usize);
+        if x >= self.size[0] || y >= self.size[1] {
+            return None;
         }
+        self.data.get(y * self.size[0] + x)
+    }
+
+    pub fn to_color_image(&self) -> Result<ColorImage> {
+        check_sample_count(self.size, &self.data)?;
+        let lut = self.colormap.lut();
+        let pixels = (0..self.data.len())
+            .filter_map(|i| self.data.get(i))
+            .map(|v| {
+                if v.is_finite() {
+                    lut[(self.window_level.normalize(v) * 255.0).round() as usize]
+                } else {
+                    Color32::TRANSPARENT
+                }
+            })
+            .collect();
+        Ok(ColorImage {
+            size: self.size,
+            pixels,
+        })
+    }
+
+    /// Texture for the current colormap. One texture is cached per colormap, so
+    /// switching back and forth between colormaps does not re-upload, and the
+    /// texture is re-uploaded in place when the window/level changes.
+    fn texture(&self, ctx: &Context) -> Result<TextureHandle> {
+        cached_texture(
+            ctx,
+            self.texture_id.with(self.colormap),
+            self.window_level,
+            || self.to_color_image(),
+        )
+    }
+
+    pub fn show(
+        &self,
+        ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let texture = self.texture(ui.ctx())?;
+        let rect = self.bounding_rect();
+        painter.image(
+            texture.id(),
+            Rect::from_two_pos(
+                canvas_state.to_screen_pos(painter, rect.min),
+                canvas_state.to_screen_pos(painter, rect.max),
+            ),
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        Ok(None)
+    }
+}
 
-        if !state.is_valid() {
-            *state = old_state;
+/// Metric depth buffer rendered through a colormap, with a depth readout under
+/// the pointer. Non-finite samples are treated as missing.
+#[derive(Debug, Clone)]
+pub struct DepthImage {
+    pub image: RawImage,
+    /// Unit suffix of the readout
+    pub unit: String,
+    pub show_readout: bool,
+}
+
+impl From<DepthImage> for Content {
+    fn from(value: DepthImage) -> Self {
+        Content::DepthImage(value)
+    }
+}
+
+impl DepthImage {
+    pub fn new(
+        texture_key: impl Hash,
+        size: [usize; 2],
+        depth: impl Into<sync::Arc<[f32]>>,
+    ) -> Self {
+        Self {
+            image: RawImage::from_f32(texture_key, size, depth)
+                .with_auto_window_level()
+                .with_colormap(Colormap::Turbo),
+            unit: "m".to_string(),
+            show_readout: true,
         }
+    }
+
+    pub fn with_position(mut self, position: Pos2) -> Self {
+        self.image.position = position;
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.image.id = Some(id);
+        self
+    }
+
+    pub fn with_depth_range(mut self, min: f32, max: f32) -> Self {
+        self.image.window_level = WindowLevel::from_min_max(min, max);
+        self
+    }
+
+    pub fn with_colormap(mut self, colormap: Colormap) -> Self {
+        self.image.colormap = colormap;
+        self
+    }
+
+    pub fn with_unit(mut self, unit: impl ToString) -> Self {
+        self.unit = unit.to_string();
+        self
+    }
+
+    pub fn with_readout(mut self, show_readout: bool) -> Self {
+        self.show_readout = show_readout;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.image.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.image.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Depth under a canvas position, if the sample is valid.
+    pub fn depth_at(&self, pos: Pos2) -> Option<f32> {
+        self.image.value_at(pos).filter(|depth| depth.is_finite())
+    }
+
+    pub fn show(
+        &self,
+        ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        self.image.show(ui, painter, canvas_state)?;
 
-        Ok(response)
+        if !self.show_readout {
+            return Ok(None);
+        }
+        let Some(pointer) = canvas_state.hover_pos else {
+            return Ok(None);
+        };
+        if let Some(depth) = self.depth_at(canvas_state.to_canvas_pos(painter, pointer)) {
+            let text = format!("{depth:.3} {}", self.unit);
+            let galley = painter.layout_no_wrap(text, FontId::default(), Color32::WHITE);
+            let rect =
+                Align2::LEFT_BOTTOM.anchor_size(pointer + Vec2::new(12.0, -12.0), galley.size());
+            painter.rect_filled(rect.expand(2.0), 2.0, Color32::from_black_alpha(180));
+            painter.galley(rect.min, galley, Color32::WHITE);
+        }
+        Ok(None)
     }
 }
 
-impl VisCanvasStateInner {
-    pub fn current_scale_vec(&self) -> Vec2 {
-        match self.origin {
-            Origin::TopLeft => Vec2::new(self.current_scale, self.current_scale),
-            Origin::BottomLeft => Vec2::new(self.current_scale, -self.current_scale),
+/// One plane of a [`MultiChannelImage`].
+#[derive(Debug, Clone)]
+pub struct Channel {
+    pub data: RawImageData,
+    pub color: Color32,
+    pub gain: f32,
+    pub window_level: WindowLevel,
+    pub visible: bool,
+}
+
+impl Channel {
+    pub fn new(data: impl Into<RawImageData>, color: Color32) -> Self {
+        let data = data.into();
+        Self {
+            window_level: data.default_window_level(),
+            data,
+            color,
+            gain: 1.0,
+            visible: true,
         }
     }
 
-    fn is_valid(&self) -> bool {
-        0.0 <= self.current_scale
-            && self.current_scale <= 10.0
-            && -100000.0 <= self.shift.x
-            && self.shift.x <= 100000.0
-            && -100000.0 <= self.shift.y
-            && self.shift.y <= 100000.0
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    pub fn with_window_level(mut self, window_level: WindowLevel) -> Self {
+        self.window_level = window_level;
+        self
+    }
+
+    pub fn with_auto_window_level(mut self) -> Self {
+        if let Some(window_level) = self.data.auto_window_level() {
+            self.window_level = window_level;
+        }
+        self
+    }
+
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    fn settings(&self) -> (Color32, f32, WindowLevel, bool) {
+        (self.color, self.gain, self.window_level, self.visible)
+    }
+}
+
+/// Additive composite of single-channel planes, each tinted with its own color
+/// (fluorescence microscopy style).
+#[derive(Debug, Clone)]
+pub struct MultiChannelImage {
+    /// Identifies the cached texture. Use a new key whenever channel data changes.
+    texture_id: Id,
+    pub id: Option<Id>,
+    /// Width and height in pixels, shared by all channels
+    pub size: [usize; 2],
+    pub channels: Vec<Channel>,
+    /// Canvas position of the top-left pixel
+    pub position: Pos2,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl From<MultiChannelImage> for Content {
+    fn from(value: MultiChannelImage) -> Self {
+        Content::MultiChannelImage(value)
+    }
+}
+
+impl MultiChannelImage {
+    /// `texture_key` identifies the cached texture; it does not make the image
+    /// pickable, use [`Self::with_id`] for that.
+    pub fn new(texture_key: impl Hash, size: [usize; 2]) -> Self {
+        Self {
+            texture_id: Id::new(texture_key),
+            id: None,
+            size,
+            channels: Vec::new(),
+            position: Pos2::ZERO,
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
+
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.channels.push(channel);
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_position(mut self, position: Pos2) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_min_size(
+            self.position,
+            Vec2::new(self.size[0] as f32, self.size[1] as f32),
+        )
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.bounding_rect().expand(tolerance).contains(pos)
+    }
+
+    pub fn to_color_image(&self) -> Result<ColorImage> {
+        let len = self.size[0] * self.size[1];
+        let mut sums = vec![[0.0f32; 3]; len];
+        for channel in self.channels.iter().filter(|channel| channel.visible) {
+            check_sample_count(self.size, &channel.data)?;
+            let [r, g, b, _] = channel.color.to_array();
+            for (i, sum) in sums.iter_mut().enumerate() {
+                let v = channel.data.get(i).unwrap_or(f32::NAN);
+                if !v.is_finite() {
+                    continue;
+                }
+                let intensity = (channel.window_level.normalize(v) * channel.gain).clamp(0.0, 1.0);
+                sum[0] += r as f32 * intensity;
+                sum[1] += g as f32 * intensity;
+                sum[2] += b as f32 * intensity;
+            }
+        }
+        let pixels = sums
+            .into_iter()
+            .map(|[r, g, b]| {
+                Color32::from_rgb(r.min(255.0) as u8, g.min(255.0) as u8, b.min(255.0) as u8)
+            })
+            .collect();
+        Ok(ColorImage {
+            size: self.size,
+            pixels,
+        })
+    }
+
+    pub fn show(
+        &self,
+        ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let settings: Vec<_> = self.channels.iter().map(Channel::settings).collect();
+        let texture = cached_texture(ui.ctx(), self.texture_id, settings, || {
+            self.to_color_image()
+        })?;
+        let rect = self.bounding_rect();
+        painter.image(
+            texture.id(),
+            Rect::from_two_pos(
+                canvas_state.to_screen_pos(painter, rect.min),
+                canvas_state.to_screen_pos(painter, rect.max),
+            ),
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        Ok(None)
+    }
+}
+
+/// Word-wrapped multi-line text in a box anchored to the canvas. The box width
+/// and font size are in canvas units and scale with the zoom; the height grows
+/// with the text.
+#[derive(Debug, Clone)]
+pub struct TextBox {
+    /// Canvas position of the top-left corner
+    pub position: Pos2,
+    pub width: f32,
+    pub text: String,
+    pub font_size: f32,
+    pub text_color: Color32,
+    pub fill_color: Option<Color32>,
+    pub stroke: Option<Stroke>,
+    pub padding: f32,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl From<TextBox> for Content {
+    fn from(value: TextBox) -> Self {
+        Content::TextBox(value)
+    }
+}
+
+impl TextBox {
+    pub fn new(position: Pos2, width: f32, text: impl ToString) -> Self {
+        Self {
+            position,
+            width,
+            text: text.to_string(),
+            font_size: 14.0,
+            text_color: Color32::BLACK,
+            fill_color: Some(Color32::from_rgb(255, 250, 205)),
+            stroke: Some(Stroke::new(1.0, Color32::GRAY)),
+            padding: 4.0,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_text_color(mut self, color: Color32) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    pub fn with_fill_color(mut self, fill_color: Color32) -> Self {
+        self.fill_color = Some(fill_color);
+        self
+    }
+
+    pub fn without_fill(mut self) -> Self {
+        self.fill_color = None;
+        self
+    }
+
+    pub fn with_stroke_color(mut self, stroke_color: Color32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.color = stroke_color;
+        } else {
+            self.stroke = Some(Stroke::new(1.0, stroke_color));
+        }
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, stroke_thickness: f32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.width = stroke_thickness;
+        } else {
+            self.stroke = Some(Stroke::new(stroke_thickness, Color32::BLACK));
+        }
+        self
+    }
+
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Counts explicit line breaks only, as wrapping depends on the fonts. The
+    /// canvas picks and fits shown text boxes by their laid-out height.
+    pub fn bounding_rect(&self) -> Rect {
+        let lines = self.text.lines().count().max(1) as f32;
+        Rect::from_min_size(
+            self.position,
+            Vec2::new(self.width, self.font_size * lines + self.padding * 2.0),
+        )
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.bounding_rect().expand(tolerance).contains(pos)
+    }
+
+    /// Screen rect and wrapped text.
+    fn layout(
+        &self,
+        painter: &Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> (Rect, sync::Arc<Galley>) {
+        let scale = canvas_state.current_scale;
+        let padding = self.padding * scale;
+        let galley = painter.layout(
+            self.text.clone(),
+            canvas_state.style().font((self.font_size * scale).max(1.0)),
+            self.text_color,
+            (self.width * scale - padding * 2.0).max(0.0),
+        );
+        let rect = Rect::from_min_size(
+            canvas_state.to_screen_pos(painter, self.position),
+            Vec2::new(self.width * scale, galley.size().y + padding * 2.0),
+        );
+        (rect, galley)
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let padding = self.padding * canvas_state.current_scale;
+        let (rect, galley) = self.layout(painter, canvas_state);
+
+        painter.rect(
+            rect,
+            Rounding::same(2.0),
+            self.fill_color.unwrap_or_default(),
+            self.stroke.unwrap_or(Stroke::new(0.0, Color32::BLACK)),
+        );
+        painter.galley(rect.min + Vec2::splat(padding), galley, self.text_color);
+
+        Ok(None)
+    }
+}
+
+/// Screen-space text balloon with a tail pointing at a canvas position. The
+/// balloon flips to the other side of the target when it would leave the canvas.
+#[derive(Debug, Clone)]
+pub struct Callout {
+    pub target: Pos2,
+    pub text: String,
+    /// Screen offset from the target to the nearest balloon corner
+    pub offset: Vec2,
+    /// Maximum balloon width in screen pixels
+    pub max_width: f32,
+    pub text_color: Color32,
+    pub fill_color: Color32,
+    pub stroke: Stroke,
+    pub padding: f32,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl From<Callout> for Content {
+    fn from(value: Callout) -> Self {
+        Content::Callout(value)
+    }
+}
+
+impl Callout {
+    pub fn new(target: Pos2, text: impl ToString) -> Self {
+        Self {
+            target,
+            text: text.to_string(),
+            offset: Vec2::new(24.0, -24.0),
+            max_width: 200.0,
+            text_color: Color32::BLACK,
+            fill_color: Color32::WHITE,
+            stroke: Stroke::new(1.0, Color32::DARK_GRAY),
+            padding: 6.0,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    pub fn with_text_color(mut self, color: Color32) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    pub fn with_fill_color(mut self, fill_color: Color32) -> Self {
+        self.fill_color = fill_color;
+        self
+    }
+
+    pub fn with_stroke_color(mut self, stroke_color: Color32) -> Self {
+        self.stroke.color = stroke_color;
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, stroke_thickness: f32) -> Self {
+        self.stroke.width = stroke_thickness;
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_min_max(self.target, self.target)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.target.distance(pos) <= tolerance
+    }
+
+    fn balloon_rect(target: Pos2, offset: Vec2, size: Vec2) -> Rect {
+        let corner = target + offset;
+        let min = Pos2::new(
+            if offset.x >= 0.0 {
+                corner.x
+            } else {
+                corner.x - size.x
+            },
+            if offset.y >= 0.0 {
+                corner.y
+            } else {
+                corner.y - size.y
+            },
+        );
+        Rect::from_min_size(min, size)
+    }
+
+    /// Balloon rect in screen coordinates, flipped to stay inside the canvas, and
+    /// the wrapped text.
+    fn layout(
+        &self,
+        painter: &Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> (Rect, sync::Arc<Galley>) {
+        let target = canvas_state.to_screen_pos(painter, self.target);
+        let galley = painter.layout(
+            self.text.clone(),
+            canvas_state.style().font(FontId::default().size),
+            self.text_color,
+            (self.max_width - self.padding * 2.0).max(0.0),
+        );
+        let size = galley.size() + Vec2::splat(self.padding * 2.0);
+
+        let clip_rect = painter.clip_rect();
+        let mut offset = self.offset;
+        let rect = Self::balloon_rect(target, offset, size);
+        if (offset.x >= 0.0 && rect.right() > clip_rect.right())
+            || (offset.x < 0.0 && rect.left() < clip_rect.left())
+        {
+            offset.x = -offset.x;
+        }
+        if (offset.y >= 0.0 && rect.bottom() > clip_rect.bottom())
+            || (offset.y < 0.0 && rect.top() < clip_rect.top())
+        {
+            offset.y = -offset.y;
+        }
+        (Self::balloon_rect(target, offset, size), galley)
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let target = canvas_state.to_screen_pos(painter, self.target);
+        let (rect, galley) = self.layout(painter, canvas_state);
+        let size = rect.size();
+
+        // The tail is drawn first so that the balloon covers its base.
+        let base_center = rect.center();
+        let direction = (target - base_center).normalized();
+        let half_width = (size.min_elem() / 3.0).min(8.0);
+        let normal = direction.rot90() * half_width;
+        painter.add(Shape::convex_polygon(
+            vec![target, base_center + normal, base_center - normal],
+            self.fill_color,
+            self.stroke,
+        ));
+        painter.rect(rect, Rounding::same(4.0), self.fill_color, self.stroke);
+        painter.galley(
+            rect.min + Vec2::splat(self.padding),
+            galley,
+            self.text_color,
+        );
+
+        Ok(None)
+    }
+}
+
+/// Small pill-shaped status marker with a short text or icon glyph, drawn at a
+/// constant screen size next to a canvas position.
+#[derive(Debug, Clone)]
+pub struct Badge {
+    pub anchor: Pos2,
+    pub text: String,
+    /// Which side of the badge touches the anchor
+    pub align: Align2,
+    /// Screen offset applied after anchoring
+    pub offset: Vec2,
+    pub font_size: f32,
+    pub text_color: Color32,
+    pub fill_color: Color32,
+    pub stroke: Option<Stroke>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
+}
+
+impl From<Badge> for Content {
+    fn from(value: Badge) -> Self {
+        Content::Badge(value)
+    }
+}
+
+impl Badge {
+    pub fn new(anchor: Pos2, text: impl ToString) -> Self {
+        Self {
+            anchor,
+            text: text.to_string(),
+            align: Align2::CENTER_CENTER,
+            offset: Vec2::ZERO,
+            font_size: 11.0,
+            text_color: Color32::WHITE,
+            fill_color: Color32::from_rgb(60, 60, 60),
+            stroke: None,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+            animation: None,
+        }
+    }
+
+    pub fn with_align(mut self, align: Align2) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_text_color(mut self, color: Color32) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    pub fn with_fill_color(mut self, fill_color: Color32) -> Self {
+        self.fill_color = fill_color;
+        self
+    }
+
+    pub fn with_stroke_color(mut self, stroke_color: Color32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.color = stroke_color;
+        } else {
+            self.stroke = Some(Stroke::new(1.0, stroke_color));
+        }
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, stroke_thickness: f32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.width = stroke_thickness;
+        } else {
+            self.stroke = Some(Stroke::new(stroke_thickness, Color32::BLACK));
+        }
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_min_max(self.anchor, self.anchor)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.anchor.distance(pos) <= tolerance
+    }
+
+    fn padding(&self) -> Vec2 {
+        Vec2::new(self.font_size * 0.5, self.font_size * 0.2)
+    }
+
+    /// Pill rect in screen coordinates and the text inside it.
+    fn layout(
+        &self,
+        painter: &Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> (Rect, sync::Arc<Galley>) {
+        let anchor = canvas_state.to_screen_pos(painter, self.anchor) + self.offset;
+        let galley = painter.layout_no_wrap(
+            self.text.clone(),
+            canvas_state.style().font(self.font_size),
+            self.text_color,
+        );
+        let size = galley.size() + self.padding() * 2.0;
+        (self.align.anchor_size(anchor, size), galley)
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let (rect, galley) = self.layout(painter, canvas_state);
+        let padding = self.padding();
+        let size = rect.size();
+
+        painter.rect(
+            rect,
+            Rounding::same(size.y / 2.0),
+            self.fill_color,
+            self.stroke.unwrap_or(Stroke::new(0.0, Color32::BLACK)),
+        );
+        painter.galley(rect.min + padding, galley, self.text_color);
+
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GaugeStyle {
+    #[default]
+    Bar,
+    Radial,
+}
+
+pub(crate) fn ramp_color(ramp: &[Color32], t: f32) -> Color32 {
+    match ramp {
+        [] => Color32::WHITE,
+        [color] => *color,
+        _ => {
+            let t = t.clamp(0.0, 1.0) * (ramp.len() - 1) as f32;
+            let i = (t.floor() as usize).min(ramp.len() - 2);
+            let f = t - i as f32;
+            let (a, b) = (ramp[i].to_array(), ramp[i + 1].to_array());
+            let mix = |c: usize| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * f).round() as u8;
+            Color32::from_rgba_premultiplied(mix(0), mix(1), mix(2), mix(3))
+        }
+    }
+}
+
+/// Value in `0.0..=1.0` shown as a bar or radial gauge at a constant screen size,
+/// centered on a canvas position.
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    pub anchor: Pos2,
+    pub value: f32,
+    pub style: GaugeStyle,
+    /// Screen size; radial gauges use the smaller side as diameter
+    pub size: Vec2,
+    /// Screen offset applied after anchoring
+    pub offset: Vec2,
+    /// Colors interpolated over the value range, from empty to full
+    pub ramp: Vec<Color32>,
+    pub background_color: Color32,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl From<Gauge> for Content {
+    fn from(value: Gauge) -> Self {
+        Content::Gauge(value)
+    }
+}
+
+impl Gauge {
+    pub fn new(anchor: Pos2, value: f32) -> Self {
+        Self {
+            anchor,
+            value,
+            style: GaugeStyle::default(),
+            size: Vec2::new(32.0, 6.0),
+            offset: Vec2::ZERO,
+            ramp: vec![Color32::RED, Color32::YELLOW, Color32::GREEN],
+            background_color: Color32::from_black_alpha(160),
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
+
+    pub fn with_style(mut self, style: GaugeStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_ramp(mut self, ramp: Vec<Color32>) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    pub fn with_background_color(mut self, color: Color32) -> Self {
+        self.background_color = color;
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_min_max(self.anchor, self.anchor)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.anchor.distance(pos) <= tolerance
+    }
+
+    /// Rect in screen coordinates that the bar or the radial arc is drawn in.
+    fn layout(&self, painter: &Painter, canvas_state: &VisCanvasStateInner) -> Rect {
+        let center = canvas_state.to_screen_pos(painter, self.anchor) + self.offset;
+        Rect::from_center_size(center, self.size)
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let rect = self.layout(painter, canvas_state);
+        let center = rect.center();
+        let value = self.value.clamp(0.0, 1.0);
+        let color = ramp_color(&self.ramp, value);
+
+        match self.style {
+            GaugeStyle::Bar => {
+                painter.rect_filled(rect, Rounding::same(1.0), self.background_color);
+                let mut filled = rect.shrink(1.0);
+                filled.set_width(filled.width() * value);
+                painter.rect_filled(filled, Rounding::same(1.0), color);
+            }
+            GaugeStyle::Radial => {
+                // 270 degree sweep, opening at the bottom
+                const START: f32 = std::f32::consts::PI * 0.75;
+                const SWEEP: f32 = std::f32::consts::PI * 1.5;
+                let diameter = rect.size().min_elem();
+                let thickness = (diameter * 0.2).max(1.0);
+                let radius = (diameter - thickness) / 2.0;
+                let arc = |fraction: f32| -> Vec<Pos2> {
+                    let steps = ((32.0 * fraction).ceil() as usize).max(1);
+                    (0..=steps)
+                        .map(|i| {
+                            let angle = START + SWEEP * fraction * i as f32 / steps as f32;
+                            center + Vec2::angled(angle) * radius
+                        })
+                        .collect()
+                };
+                painter.add(PathShape::line(
+                    arc(1.0),
+                    Stroke::new(thickness, self.background_color),
+                ));
+                if value > 0.0 {
+                    painter.add(PathShape::line(arc(value), Stroke::new(thickness, color)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Key/value rows in a small card drawn at a constant screen size next to a
+/// canvas position, e.g. per-object statistics.
+#[derive(Debug, Clone)]
+pub struct MiniTable {
+    pub anchor: Pos2,
+    pub rows: Vec<(String, String)>,
+    /// Which side of the card touches the anchor
+    pub align: Align2,
+    /// Screen offset applied after anchoring
+    pub offset: Vec2,
+    /// Maximum card width in screen pixels; long values wrap
+    pub max_width: f32,
+    pub font_size: f32,
+    pub key_color: Color32,
+    pub value_color: Color32,
+    pub fill_color: Color32,
+    pub stroke: Option<Stroke>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl From<MiniTable> for Content {
+    fn from(value: MiniTable) -> Self {
+        Content::MiniTable(value)
+    }
+}
+
+/// Key and value galleys of each [`MiniTable`] row.
+type TableRows = Vec<(sync::Arc<Galley>, sync::Arc<Galley>)>;
+
+impl MiniTable {
+    pub fn new(anchor: Pos2) -> Self {
+        Self {
+            anchor,
+            rows: Vec::new(),
+            align: Align2::LEFT_TOP,
+            offset: Vec2::new(8.0, 8.0),
+            max_width: 200.0,
+            font_size: 11.0,
+            key_color: Color32::GRAY,
+            value_color: Color32::WHITE,
+            fill_color: Color32::from_black_alpha(200),
+            stroke: None,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
+
+    pub fn with_row(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.rows.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_align(mut self, align: Align2) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_key_color(mut self, color: Color32) -> Self {
+        self.key_color = color;
+        self
+    }
+
+    pub fn with_value_color(mut self, color: Color32) -> Self {
+        self.value_color = color;
+        self
+    }
+
+    pub fn with_fill_color(mut self, fill_color: Color32) -> Self {
+        self.fill_color = fill_color;
+        self
+    }
+
+    pub fn with_stroke_color(mut self, stroke_color: Color32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.color = stroke_color;
+        } else {
+            self.stroke = Some(Stroke::new(1.0, stroke_color));
+        }
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_min_max(self.anchor, self.anchor)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.anchor.distance(pos) <= tolerance
+    }
+
+    /// Card rect in screen coordinates, the key column width and the key/value
+    /// galleys of each row, or `None` without rows.
+    fn layout(
+        &self,
+        painter: &Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Option<(Rect, f32, TableRows)> {
+        if self.rows.is_empty() {
+            return None;
+        }
+        let font_id = canvas_state.style().font(self.font_size);
+        let padding = self.font_size * 0.5;
+        let gap = self.font_size;
+
+        let keys: Vec<_> = self
+            .rows
+            .iter()
+            .map(|(key, _)| painter.layout_no_wrap(key.clone(), font_id.clone(), self.key_color))
+            .collect();
+        let key_width = keys
+            .iter()
+            .map(|galley| galley.size().x)
+            .fold(0.0, f32::max)
+            .min(self.max_width / 2.0);
+        let value_wrap_width = (self.max_width - key_width - gap - padding * 2.0).max(0.0);
+        let values: Vec<_> = self
+            .rows
+            .iter()
+            .map(|(_, value)| {
+                painter.layout(
+                    value.clone(),
+                    font_id.clone(),
+                    self.value_color,
+                    value_wrap_width,
+                )
+            })
+            .collect();
+
+        let value_width = values
+            .iter()
+            .map(|galley| galley.size().x)
+            .fold(0.0, f32::max);
+        let row_heights: Vec<f32> = keys
+            .iter()
+            .zip(&values)
+            .map(|(key, value)| key.size().y.max(value.size().y))
+            .collect();
+        let size = Vec2::new(
+            key_width + gap + value_width + padding * 2.0,
+            row_heights.iter().sum::<f32>() + padding * 2.0,
+        );
+
+        let anchor = canvas_state.to_screen_pos(painter, self.anchor) + self.offset;
+        let rect = self.align.anchor_size(anchor, size);
+        Some((rect, key_width, keys.into_iter().zip(values).collect()))
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let Some((rect, key_width, rows)) = self.layout(painter, canvas_state) else {
+            return Ok(None);
+        };
+        let padding = self.font_size * 0.5;
+        let gap = self.font_size;
+        painter.rect(
+            rect,
+            Rounding::same(3.0),
+            self.fill_color,
+            self.stroke.unwrap_or(Stroke::new(0.0, Color32::BLACK)),
+        );
+
+        let mut y = rect.top() + padding;
+        for (key, value) in rows {
+            let x = rect.left() + padding;
+            let height = key.size().y.max(value.size().y);
+            painter.galley(Pos2::new(x, y), key, self.key_color);
+            painter.galley(Pos2::new(x + key_width + gap, y), value, self.value_color);
+            y += height;
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SparklineStyle {
+    #[default]
+    Line,
+    Bar,
+}
+
+/// Small chart of a value series drawn at a constant screen size next to a
+/// canvas position, e.g. per-object score history.
+#[derive(Debug, Clone)]
+pub struct Sparkline {
+    pub anchor: Pos2,
+    pub values: Vec<f32>,
+    pub style: SparklineStyle,
+    /// Value mapped to the bottom and top of the chart; `None` uses the data range
+    pub range: Option<(f32, f32)>,
+    /// Screen size of the chart
+    pub size: Vec2,
+    /// Which side of the chart touches the anchor
+    pub align: Align2,
+    /// Screen offset applied after anchoring
+    pub offset: Vec2,
+    pub stroke: Stroke,
+    pub background_color: Option<Color32>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl From<Sparkline> for Content {
+    fn from(value: Sparkline) -> Self {
+        Content::Sparkline(value)
+    }
+}
+
+impl Sparkline {
+    pub fn new(anchor: Pos2, values: Vec<f32>) -> Self {
+        Self {
+            anchor,
+            values,
+            style: SparklineStyle::default(),
+            range: None,
+            size: Vec2::new(60.0, 20.0),
+            align: Align2::LEFT_BOTTOM,
+            offset: Vec2::new(4.0, -4.0),
+            stroke: Stroke::new(1.0, Color32::from_rgb(0, 120, 255)),
+            background_color: Some(Color32::from_white_alpha(200)),
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+        }
+    }
+
+    pub fn with_style(mut self, style: SparklineStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_align(mut self, align: Align2) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_stroke_color(mut self, color: Color32) -> Self {
+        self.stroke.color = color;
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, thickness: f32) -> Self {
+        self.stroke.width = thickness;
+        self
+    }
+
+    pub fn with_background_color(mut self, color: Option<Color32>) -> Self {
+        self.background_color = color;
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_min_max(self.anchor, self.anchor)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.anchor.distance(pos) <= tolerance
+    }
+
+    /// Chart rect in screen coordinates.
+    fn layout(&self, painter: &Painter, canvas_state: &VisCanvasStateInner) -> Rect {
+        let anchor = canvas_state.to_screen_pos(painter, self.anchor) + self.offset;
+        self.align.anchor_size(anchor, self.size)
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let rect = self.layout(painter, canvas_state);
+        if let Some(background_color) = self.background_color {
+            painter.rect_filled(rect.expand(1.0), Rounding::same(2.0), background_color);
+        }
+        if self.values.is_empty() {
+            return Ok(None);
+        }
+
+        let (min, max) = self.range.unwrap_or_else(|| {
+            self.values
+                .iter()
+                .filter(|v| v.is_finite())
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &v| {
+                    (min.min(v), max.max(v))
+                })
+        });
+        let height_of = |v: f32| {
+            if max > min {
+                ((v - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.5
+            }
+        };
+
+        match self.style {
+            SparklineStyle::Line => {
+                let step = rect.width() / (self.values.len().max(2) - 1) as f32;
+                let points = self
+                    .values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| v.is_finite())
+                    .map(|(i, &v)| {
+                        Pos2::new(
+                            rect.left() + step * i as f32,
+                            rect.bottom() - rect.height() * height_of(v),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if points.len() >= 2 {
+                    painter.add(PathShape::line(points, self.stroke));
+                }
+            }
+            SparklineStyle::Bar => {
+                let bar_width = rect.width() / self.values.len() as f32;
+                for (i, &v) in self.values.iter().enumerate() {
+                    if !v.is_finite() {
+                        continue;
+                    }
+                    let left = rect.left() + bar_width * i as f32;
+                    let bar = Rect::from_min_max(
+                        Pos2::new(left, rect.bottom() - rect.height() * height_of(v)),
+                        Pos2::new(left + (bar_width - 1.0).max(1.0), rect.bottom()),
+                    );
+                    painter.rect_filled(bar, Rounding::ZERO, self.stroke.color);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Polygon {
+    /// Vertices in canvas coordinates; the path is closed automatically
+    pub vertices: Vec<Pos2>,
+    pub fill_color: Option<Color32>,
+    pub stroke: Option<Stroke>,
+    /// Only the join applies to the closed outline
+    pub stroke_style: StrokeStyle,
+    pub label: Option<String>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
+}
+
+impl From<Polygon> for Content {
+    fn from(polygon: Polygon) -> Self {
+        Content::Polygon(polygon)
+    }
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Pos2>) -> Self {
+        Self {
+            vertices,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_fill_color(mut self, fill_color: Color32) -> Self {
+        self.fill_color = Some(fill_color);
+        self
+    }
+
+    pub fn with_stroke_color(mut self, stroke_color: Color32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.color = stroke_color;
+        } else {
+            self.stroke = Some(Stroke::new(1.0, stroke_color));
+        }
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, stroke_thickness: f32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.width = stroke_thickness;
+        } else {
+            self.stroke = Some(Stroke::new(stroke_thickness, Color32::BLACK));
+        }
+        self
+    }
+
+    pub fn with_line_join(mut self, join: LineJoin) -> Self {
+        self.stroke_style.join = join;
+        self
+    }
+
+    pub fn with_label(mut self, label: impl ToString) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_points(&self.vertices)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        point_in_polygon(pos, &self.vertices)
+            || (0..self.vertices.len()).any(|i| {
+                let next = self.vertices[(i + 1) % self.vertices.len()];
+                distance_to_segment(pos, self.vertices[i], next) <= tolerance
+            })
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        if self.vertices.len() < 2 {
+            return Ok(None);
+        }
+        let points: Vec<Pos2> = self
+            .vertices
+            .iter()
+            .map(|&vertex| canvas_state.to_screen_pos(painter, vertex))
+            .collect();
+
+        if let Some(fill_color) = self.fill_color {
+            let mut mesh = Mesh::default();
+            for &point in &points {
+                mesh.colored_vertex(point, fill_color);
+            }
+            for [a, b, c] in triangulate(&self.vertices) {
+                mesh.add_triangle(a as u32, b as u32, c as u32);
+            }
+            painter.add(Shape::mesh(mesh));
+        }
+        if let Some(stroke) = self.stroke {
+            if self.stroke_style.is_default() {
+                painter.add(PathShape::closed_line(points.clone(), stroke));
+            } else {
+                painter.add(Shape::mesh(polyline_mesh(
+                    &points,
+                    true,
+                    stroke,
+                    self.stroke_style,
+                )));
+            }
+        }
+        if let Some(label) = &self.label {
+            let anchor = Rect::from_points(&points).left_top();
+            canvas_state.style().paint_label(
+                painter,
+                anchor,
+                Align2::LEFT_BOTTOM,
+                label,
+                self.fill_color,
+            );
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Ellipse {
+    pub center: Pos2,
+    /// Semi-axis lengths along the ellipse's own x and y axes
+    pub radii: Vec2,
+    /// Rotation of the x semi-axis in radians
+    pub rotation: f32,
+    pub fill_color: Option<Color32>,
+    pub stroke: Option<Stroke>,
+    pub label: Option<String>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
+}
+
+impl From<Ellipse> for Content {
+    fn from(ellipse: Ellipse) -> Self {
+        Content::Ellipse(ellipse)
+    }
+}
+
+impl Ellipse {
+    pub fn new(center: Pos2, radii: Vec2) -> Self {
+        Self {
+            center,
+            radii,
+            ..Default::default()
+        }
+    }
+
+    /// Confidence ellipse of a 2D Gaussian: `covariance` is `[[xx, xy], [xy, yy]]`
+    /// and the semi-axes span `n_sigma` standard deviations.
+    pub fn from_covariance(mean: Pos2, covariance: [[f32; 2]; 2], n_sigma: f32) -> Self {
+        let [[xx, xy], [_, yy]] = covariance;
+        let half_trace = (xx + yy) / 2.0;
+        let discriminant = (((xx - yy) / 2.0).powi(2) + xy * xy).sqrt();
+        let major = (half_trace + discriminant).max(0.0);
+        let minor = (half_trace - discriminant).max(0.0);
+        Self {
+            center: mean,
+            radii: Vec2::new(major.sqrt(), minor.sqrt()) * n_sigma,
+            rotation: 0.5 * (2.0 * xy).atan2(xx - yy),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_fill_color(mut self, fill_color: Color32) -> Self {
+        self.fill_color = Some(fill_color);
+        self
+    }
+
+    pub fn with_stroke_color(mut self, stroke_color: Color32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.color = stroke_color;
+        } else {
+            self.stroke = Some(Stroke::new(1.0, stroke_color));
+        }
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, stroke_thickness: f32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.width = stroke_thickness;
+        } else {
+            self.stroke = Some(Stroke::new(stroke_thickness, Color32::BLACK));
+        }
+        self
+    }
+
+    pub fn with_label(mut self, label: impl ToString) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    fn axes(&self) -> (Vec2, Vec2) {
+        let x_axis = Vec2::angled(self.rotation);
+        (x_axis * self.radii.x, x_axis.rot90() * self.radii.y)
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        let (a, b) = self.axes();
+        let half_extent = Vec2::new(a.x.hypot(b.x), a.y.hypot(b.y));
+        Rect::from_center_size(self.center, half_extent * 2.0)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        let d = pos - self.center;
+        let x_axis = Vec2::angled(self.rotation);
+        let local = Vec2::new(d.dot(x_axis), d.dot(x_axis.rot90()));
+        let radii = self.radii + Vec2::splat(tolerance);
+        if radii.x <= 0.0 || radii.y <= 0.0 {
+            return false;
+        }
+        (local.x / radii.x).powi(2) + (local.y / radii.y).powi(2) <= 1.0
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let (a, b) = self.axes();
+        let screen_radius = self.radii.max_elem() * canvas_state.current_scale;
+        let steps = ((screen_radius * std::f32::consts::TAU / 4.0).ceil() as usize).clamp(16, 256);
+        let points: Vec<Pos2> = (0..steps)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / steps as f32;
+                let pos = self.center + a * angle.cos() + b * angle.sin();
+                canvas_state.to_screen_pos(painter, pos)
+            })
+            .collect();
+
+        painter.add(Shape::convex_polygon(
+            points,
+            self.fill_color.unwrap_or_default(),
+            if let Some(stroke) = &self.stroke {
+                *stroke
+            } else {
+                Stroke::new(0.0, Color32::BLACK)
+            },
+        ));
+        if let Some(label) = &self.label {
+            let center = canvas_state.to_screen_pos(painter, self.center);
+            canvas_state.style().paint_label(
+                painter,
+                center,
+                Align2::CENTER_CENTER,
+                label,
+                self.fill_color,
+            );
+        }
+
+        Ok(None)
+    }
+}
+
+/// Cubic bezier curve through `start` and `end`, pulled towards two control points.
+#[derive(Debug, Clone)]
+pub struct CubicBezier {
+    /// Start, control points and end in canvas coordinates
+    pub points: [Pos2; 4],
+    pub stroke: Stroke,
+    pub chevrons: Option<Chevrons>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
+}
+
+impl From<CubicBezier> for Content {
+    fn from(bezier: CubicBezier) -> Self {
+        Content::CubicBezier(bezier)
+    }
+}
+
+impl CubicBezier {
+    pub fn new(start: Pos2, control1: Pos2, control2: Pos2, end: Pos2) -> Self {
+        Self {
+            points: [start, control1, control2, end],
+            stroke: Stroke::new(1.0, Color32::BLACK),
+            chevrons: None,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+            animation: None,
+        }
+    }
+
+    pub fn with_stroke_color(mut self, color: Color32) -> Self {
+        self.stroke.color = color;
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, thickness: f32) -> Self {
+        self.stroke.width = thickness;
+        self
+    }
+
+    pub fn with_chevrons(mut self, chevrons: Chevrons) -> Self {
+        self.chevrons = Some(chevrons);
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    fn shape(&self, points: [Pos2; 4], stroke: Stroke) -> CubicBezierShape {
+        CubicBezierShape::from_points_stroke(points, false, Color32::TRANSPARENT, stroke)
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        self.shape(self.points, Stroke::NONE)
+            .logical_bounding_rect()
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        let points = self
+            .shape(self.points, Stroke::NONE)
+            .flatten(Some((tolerance * 0.1).max(0.01)));
+        distance_to_polyline(pos, &points) <= tolerance
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let points = self
+            .points
+            .map(|pos| canvas_state.to_screen_pos(painter, pos));
+        let shape = self.shape(points, self.stroke);
+        let chevrons = self.chevrons.as_ref().map(|chevrons| {
+            chevrons.shapes(
+                &shape.flatten(Some(0.5)),
+                canvas_state.current_scale,
+                self.stroke,
+                painter.clip_rect(),
+            )
+        });
+        painter.add(shape);
+        painter.extend(chevrons.unwrap_or_default());
+
+        Ok(None)
+    }
+}
+
+/// Quadratic bezier curve through `start` and `end`, pulled towards one control point.
+#[derive(Debug, Clone)]
+pub struct QuadraticBezier {
+    /// Start, control point and end in canvas coordinates
+    pub points: [Pos2; 3],
+    pub stroke: Stroke,
+    pub chevrons: Option<Chevrons>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
+}
+
+impl From<QuadraticBezier> for Content {
+    fn from(bezier: QuadraticBezier) -> Self {
+        Content::QuadraticBezier(bezier)
+    }
+}
+
+impl QuadraticBezier {
+    pub fn new(start: Pos2, control: Pos2, end: Pos2) -> Self {
+        Self {
+            points: [start, control, end],
+            stroke: Stroke::new(1.0, Color32::BLACK),
+            chevrons: None,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+            animation: None,
+        }
+    }
+
+    pub fn with_stroke_color(mut self, color: Color32) -> Self {
+        self.stroke.color = color;
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, thickness: f32) -> Self {
+        self.stroke.width = thickness;
+        self
+    }
+
+    pub fn with_chevrons(mut self, chevrons: Chevrons) -> Self {
+        self.chevrons = Some(chevrons);
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    fn shape(&self, points: [Pos2; 3], stroke: Stroke) -> QuadraticBezierShape {
+        QuadraticBezierShape::from_points_stroke(points, false, Color32::TRANSPARENT, stroke)
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        self.shape(self.points, Stroke::NONE)
+            .logical_bounding_rect()
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        let points = self
+            .shape(self.points, Stroke::NONE)
+            .flatten(Some((tolerance * 0.1).max(0.01)));
+        distance_to_polyline(pos, &points) <= tolerance
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let points = self
+            .points
+            .map(|pos| canvas_state.to_screen_pos(painter, pos));
+        let shape = self.shape(points, self.stroke);
+        let chevrons = self.chevrons.as_ref().map(|chevrons| {
+            chevrons.shapes(
+                &shape.flatten(Some(0.5)),
+                canvas_state.current_scale,
+                self.stroke,
+                painter.clip_rect(),
+            )
+        });
+        painter.add(shape);
+        painter.extend(chevrons.unwrap_or_default());
+
+        Ok(None)
+    }
+}
+
+/// Circular arc, optionally closed through the center into a pie slice.
+/// Angles are in radians, measured from the canvas x axis towards the y axis.
+#[derive(Debug, Clone, Default)]
+pub struct Arc {
+    pub center: Pos2,
+    pub radius: f32,
+    pub start_angle: f32,
+    pub end_angle: f32,
+    /// Close the arc through the center; `fill_color` is only used for pie slices
+    pub pie: bool,
+    pub fill_color: Option<Color32>,
+    pub stroke: Option<Stroke>,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
+}
+
+impl From<Arc> for Content {
+    fn from(arc: Arc) -> Self {
+        Content::Arc(arc)
+    }
+}
+
+impl Arc {
+    pub fn new(center: Pos2, radius: f32, start_angle: f32, end_angle: f32) -> Self {
+        Self {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_pie(mut self) -> Self {
+        self.pie = true;
+        self
+    }
+
+    pub fn with_fill_color(mut self, fill_color: Color32) -> Self {
+        self.fill_color = Some(fill_color);
+        self
+    }
+
+    pub fn with_stroke_color(mut self, stroke_color: Color32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.color = stroke_color;
+        } else {
+            self.stroke = Some(Stroke::new(1.0, stroke_color));
+        }
+        self
+    }
+
+    pub fn with_stroke_thickness(mut self, stroke_thickness: f32) -> Self {
+        if let Some(stroke) = &mut self.stroke {
+            stroke.width = stroke_thickness;
+        } else {
+            self.stroke = Some(Stroke::new(stroke_thickness, Color32::BLACK));
+        }
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    fn sweep(&self) -> f32 {
+        (self.end_angle - self.start_angle).clamp(-std::f32::consts::TAU, std::f32::consts::TAU)
+    }
+
+    /// Points along the arc in canvas coordinates.
+    fn arc_points(&self, steps: usize) -> Vec<Pos2> {
+        let sweep = self.sweep();
+        (0..=steps)
+            .map(|i| {
+                let angle = self.start_angle + sweep * i as f32 / steps as f32;
+                self.center + Vec2::angled(angle) * self.radius
+            })
+            .collect()
+    }
+
+    fn outline(&self, steps: usize) -> Vec<Pos2> {
+        let mut points = self.arc_points(steps);
+        if self.pie {
+            points.push(self.center);
+            points.push(points[0]);
+        }
+        points
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_points(&self.outline(64))
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        if distance_to_polyline(pos, &self.outline(64)) <= tolerance {
+            return true;
+        }
+        if !self.pie || pos.distance(self.center) > self.radius {
+            return false;
+        }
+        let sweep = self.sweep();
+        let (start, sweep) = if sweep < 0.0 {
+            (self.start_angle + sweep, -sweep)
+        } else {
+            (self.start_angle, sweep)
+        };
+        ((pos - self.center).angle() - start).rem_euclid(std::f32::consts::TAU) <= sweep
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let screen_radius = self.radius * canvas_state.current_scale;
+        let steps = ((screen_radius * self.sweep().abs() / 4.0).ceil() as usize).clamp(4, 256);
+        let points: Vec<Pos2> = self
+            .outline(steps)
+            .into_iter()
+            .map(|point| canvas_state.to_screen_pos(painter, point))
+            .collect();
+
+        if let Some(fill_color) = self.fill_color.filter(|_| self.pie) {
+            let center = canvas_state.to_screen_pos(painter, self.center);
+            let mut mesh = Mesh::default();
+            mesh.colored_vertex(center, fill_color);
+            for &point in &points[..=steps] {
+                mesh.colored_vertex(point, fill_color);
+            }
+            for i in 1..=steps as u32 {
+                mesh.add_triangle(0, i, i + 1);
+            }
+            painter.add(Shape::mesh(mesh));
+        }
+        if let Some(stroke) = self.stroke {
+            painter.add(PathShape::line(points, stroke));
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Marker {
+    #[default]
+    Circle,
+    Square,
+    /// Upright plus sign
+    Cross,
+    Diamond,
+}
+
+/// Many markers of one style, painted as a single mesh. Markers keep a constant
+/// screen size; points outside the visible area are skipped.
+#[derive(Debug, Clone)]
+pub struct Points {
+    pub positions: Vec<Pos2>,
+    pub marker: Marker,
+    /// Marker width in screen pixels
+    pub size: f32,
+    pub color: Color32,
+    pub id: Option<Id>,
+    pub draggable: bool,
+    pub z_index: i32,
+    /// Visible only while the canvas scale is within this range
+    pub zoom_range: Option<RangeInclusive<f32>>,
+    pub animation: Option<StyleAnimation>,
+}
+
+impl From<Points> for Content {
+    fn from(points: Points) -> Self {
+        Content::Points(points)
+    }
+}
+
+impl Points {
+    pub fn new(positions: Vec<Pos2>) -> Self {
+        Self {
+            positions,
+            marker: Marker::Circle,
+            size: 4.0,
+            color: Color32::BLACK,
+            id: None,
+            draggable: false,
+            z_index: 0,
+            zoom_range: None,
+            animation: None,
+        }
+    }
+
+    pub fn with_marker(mut self, marker: Marker) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_draggable(mut self) -> Self {
+        self.draggable = true;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_zoom_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.zoom_range = Some(min_scale..=max_scale);
+        self
+    }
+
+    /// Smoothly oscillates the opacity with the given period in seconds.
+    pub fn with_pulse(mut self, period: f32) -> Self {
+        self.animation = Some(StyleAnimation::Pulse { period });
+        self
+    }
+
+    /// Toggles visibility once per second.
+    pub fn with_blink(mut self) -> Self {
+        self.animation = Some(StyleAnimation::Blink { period: 1.0 });
+        self
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_points(&self.positions)
+    }
+
+    pub fn hit_test(&self, pos: Pos2, tolerance: f32) -> bool {
+        self.positions
+            .iter()
+            .any(|position| position.distance(pos) <= tolerance)
+    }
+
+    /// Marker outline around the origin as one or more convex polygons.
+    fn marker_polygons(&self) -> Vec<Vec<Vec2>> {
+        let r = self.size / 2.0;
+        match self.marker {
+            Marker::Circle => vec![(0..12)
+                .map(|i| Vec2::angled(std::f32::consts::TAU * i as f32 / 12.0) * r)
+                .collect()],
+            Marker::Square => vec![vec![
+                Vec2::new(-r, -r),
+                Vec2::new(r, -r),
+                Vec2::new(r, r),
+                Vec2::new(-r, r),
+            ]],
+            Marker::Diamond => vec![vec![
+                Vec2::new(0.0, -r),
+                Vec2::new(r, 0.0),
+                Vec2::new(0.0, r),
+                Vec2::new(-r, 0.0),
+            ]],
+            Marker::Cross => {
+                let w = (self.size / 8.0).max(0.5);
+                vec![
+                    vec![
+                        Vec2::new(-r, -w),
+                        Vec2::new(r, -w),
+                        Vec2::new(r, w),
+                        Vec2::new(-r, w),
+                    ],
+                    vec![
+                        Vec2::new(-w, -r),
+                        Vec2::new(w, -r),
+                        Vec2::new(w, r),
+                        Vec2::new(-w, r),
+                    ],
+                ]
+            }
+        }
+    }
+
+    pub fn show(
+        &self,
+        _ui: &mut Ui,
+        painter: &mut Painter,
+        canvas_state: &VisCanvasStateInner,
+    ) -> Result<Option<Response>> {
+        let polygons = self.marker_polygons();
+        let visible = painter.clip_rect().expand(self.size);
+        let mut mesh = Mesh::default();
+        for &position in &self.positions {
+            let center = canvas_state.to_screen_pos(painter, position);
+            if !visible.contains(center) {
+                continue;
+            }
+            for polygon in &polygons {
+                let base = mesh.vertices.len() as u32;
+                for &offset in polygon {
+                    mesh.colored_vertex(center + offset, self.color);
+                }
+                for i in 1..polygon.len() as u32 - 1 {
+                    mesh.add_triangle(base, base + i, base + i + 1);
+                }
+            }
+        }
+        painter.add(Shape::mesh(mesh));
+
+        Ok(None)
+    }
+}
+
+/// Pointer interaction with a content that has an id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentResponse {
+    pub id: Id,
+    pub hovered: bool,
+    pub clicked: bool,
+    pub double_clicked: bool,
+}
+
+/// Movement of a draggable content, see [`Content::is_draggable`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragEvent {
+    pub id: Id,
+    /// Where the minimum corner of the content's bounding box should move to,
+    /// in canvas coordinates
+    pub new_position: Pos2,
+    /// Pointer movement since the last frame in canvas coordinates
+    pub delta: Vec2,
+    /// Set on the frame the button is released
+    pub finished: bool,
+}
+
+pub struct VisCanvasOutput {
+    pub response: Response,
+    pub state: VisCanvasState,
+    /// One entry per content with an id, in slice order. Only the topmost
+    /// content under the pointer is hovered.
+    pub content_responses: Vec<ContentResponse>,
+    /// Ids of the selected contents, in the order they were selected.
+    /// Always empty unless [`VisCanvas::selection`] is enabled.
+    pub selected: Vec<Id>,
+    /// The content being dragged this frame, if any. The canvas does not move
+    /// contents itself; apply `new_position` to the data behind `contents`.
+    pub drag: Option<DragEvent>,
+}
+
+/// What the mouse wheel does without modifiers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScrollAction {
+    /// Wheel pans
+    #[default]
+    Pan,
+    /// Wheel zooms
+    Zoom,
+}
+
+/// What double-clicking the canvas background does.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DoubleClickAction {
+    #[default]
+    None,
+    /// Back to scale 1, see [`VisCanvasState::reset_view`]
+    ResetView,
+    /// See [`VisCanvasState::fit_contents`]
+    FitContents { padding: f32 },
+}
+
+/// Pointer bindings of a [`VisCanvas`].
+#[derive(Debug, Clone)]
+pub struct CanvasInteraction {
+    /// Button that pans the view while dragged
+    pub pan_button: PointerButton,
+    pub scroll: ScrollAction,
+    /// Whether ctrl (cmd on macOS) + wheel zooms
+    pub ctrl_to_zoom: bool,
+    /// Multiplier for wheel panning and wheel zooming
+    pub scroll_speed: f32,
+    /// Exponent applied to each zoom step; `2.0` zooms twice as fast
+    pub zoom_speed: f32,
+    /// Arrow keys pan, `+`/`-` zoom and `0`/`Home` reset while the canvas has focus.
+    /// The canvas takes focus when clicked. Off by default.
+    pub keyboard: bool,
+    /// Arrow key pan distance in screen pixels
+    pub key_pan_step: f32,
+    /// Zoom factor of one `+` press
+    pub key_zoom_step: f32,
+    /// Ignored when the double click lands on a content with an id. Does nothing
+    /// by default.
+    pub double_click: DoubleClickAction,
+    /// Keeps panning after a drag is released, slowing down by this rate per
+    /// second. `None` (the default) stops immediately.
+    pub kinetic_friction: Option<f32>,
+}
+
+impl Default for CanvasInteraction {
+    fn default() -> Self {
+        Self {
+            pan_button: PointerButton::Middle,
+            scroll: ScrollAction::Pan,
+            ctrl_to_zoom: true,
+            scroll_speed: SCROLL_SPEED,
+            zoom_speed: ZOOM_SPEED,
+            keyboard: false,
+            key_pan_step: 32.0,
+            key_zoom_step: 1.25,
+            double_click: DoubleClickAction::None,
+            kinetic_friction: None,
+        }
+    }
+}
+
+impl CanvasInteraction {
+    pub fn with_pan_button(mut self, pan_button: PointerButton) -> Self {
+        self.pan_button = pan_button;
+        self
+    }
+
+    pub fn with_scroll(mut self, scroll: ScrollAction) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    pub fn with_ctrl_to_zoom(mut self, ctrl_to_zoom: bool) -> Self {
+        self.ctrl_to_zoom = ctrl_to_zoom;
+        self
+    }
+
+    pub fn with_scroll_speed(mut self, scroll_speed: f32) -> Self {
+        self.scroll_speed = scroll_speed;
+        self
+    }
+
+    pub fn with_zoom_speed(mut self, zoom_speed: f32) -> Self {
+        self.zoom_speed = zoom_speed;
+        self
+    }
+
+    pub fn with_keyboard(mut self, keyboard: bool) -> Self {
+        self.keyboard = keyboard;
+        self
+    }
+
+    pub fn with_key_pan_step(mut self, key_pan_step: f32) -> Self {
+        self.key_pan_step = key_pan_step;
+        self
+    }
+
+    pub fn with_key_zoom_step(mut self, key_zoom_step: f32) -> Self {
+        self.key_zoom_step = key_zoom_step;
+        self
+    }
+
+    pub fn with_double_click(mut self, double_click: DoubleClickAction) -> Self {
+        self.double_click = double_click;
+        self
+    }
+
+    pub fn with_kinetic_friction(mut self, kinetic_friction: f32) -> Self {
+        self.kinetic_friction = Some(kinetic_friction);
+        self
+    }
+}
+
+/// Text appearance of content labels.
+///
+/// Labels are laid out by egui, left to right and without complex shaping, so
+/// right-to-left scripts are shown in logical order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanvasStyle {
+    /// Also sets the family of [`TextBox`], [`Callout`], [`Badge`] and [`MiniTable`]
+    pub label_font: FontId,
+    pub label_color: Color32,
+    /// Halo painted around each glyph so labels stay readable over busy imagery.
+    /// `width` is in screen pixels. `None` (the default) keeps the plain label
+    /// on a rect filled with the content's fill color.
+    pub label_outline: Option<Stroke>,
+}
+
+impl Default for CanvasStyle {
+    fn default() -> Self {
+        Self {
+            label_font: FontId::default(),
+            label_color: Color32::BLACK,
+            label_outline: None,
+        }
+    }
+}
+
+impl CanvasStyle {
+    pub fn with_label_font(mut self, font: FontId) -> Self {
+        self.label_font = font;
+        self
+    }
+
+    pub fn with_label_family(mut self, family: FontFamily) -> Self {
+        self.label_font.family = family;
+        self
+    }
+
+    /// Adds a family named `name` to `definitions` that looks glyphs up in `fonts`
+    /// in order and then in the proportional fonts. Names missing from
+    /// `definitions.font_data` are skipped. Install the definitions once with
+    /// [`Context::set_fonts`] and pass the returned family to
+    /// [`Self::with_label_family`].
+    pub fn add_label_family(
+        definitions: &mut FontDefinitions,
+        name: &str,
+        fonts: impl IntoIterator<Item = String>,
+    ) -> FontFamily {
+        let mut chain: Vec<String> = fonts
+            .into_iter()
+            .filter(|font| definitions.font_data.contains_key(font))
+            .collect();
+        chain.extend(
+            definitions
+                .families
+                .get(&FontFamily::Proportional)
+                .cloned()
+                .unwrap_or_default(),
+        );
+        let family = FontFamily::Name(name.into());
+        definitions.families.insert(family.clone(), chain);
+        family
+    }
+
+    /// Font of the label family at `size`, used by the text contents.
+    pub fn font(&self, size: f32) -> FontId {
+        FontId::new(size, self.label_font.family.clone())
+    }
+
+    pub fn with_label_color(mut self, color: Color32) -> Self {
+        self.label_color = color;
+        self
+    }
+
+    pub fn with_label_outline(mut self, outline: Stroke) -> Self {
+        self.label_outline = Some(outline);
+        self
+    }
+
+    pub fn without_label_outline(mut self) -> Self {
+        self.label_outline = None;
+        self
+    }
+
+    /// Paints `text` anchored at `pos` in screen coordinates and returns its rect.
+    /// Without a label outline, the text sits on a rect filled with `background`.
+    pub fn paint_label(
+        &self,
+        painter: &Painter,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        background: Option<Color32>,
+    ) -> Rect {
+        let galley =
+            painter.layout_no_wrap(text.to_string(), self.label_font.clone(), self.label_color);
+        let rect = anchor.anchor_size(pos, galley.size());
+        let outline = self.label_outline.filter(|outline| outline.width > 0.0);
+        if let (None, Some(background)) = (outline, background) {
+            painter.rect_filled(rect, 0.0, background);
+        }
+        if let Some(outline) = outline {
+            // Eight offset copies approximate a stroke around the glyphs.
+            for i in 0..8 {
+                let offset = Rot2::from_angle(i as f32 * std::f32::consts::FRAC_PI_4)
+                    * Vec2::new(outline.width, 0.0);
+                painter.galley_with_override_text_color(
+                    rect.min + offset,
+                    galley.clone(),
+                    outline.color,
+                );
+            }
+        }
+        painter.galley(rect.min, galley, self.label_color);
+        rect
+    }
+}
+
+/// Pan/zoom canvas widget.
+///
+/// ```ignore
+/// VisCanvas::new(Id::new("canvas"))
+///     .origin(Origin::BottomLeft)
+///     .max_zoom(20.0)
+///     .show(ui, &contents)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct VisCanvas {
+    id: Id,
+    origin: Origin,
+    min_zoom: f32,
+    max_zoom: f32,
+    background: Option<Color32>,
+    auto_fit: Option<f32>,
+    pan_bounds: Option<Rect>,
+    interaction: CanvasInteraction,
+    selection: Option<Stroke>,
+    style: CanvasStyle,
+}
+
+impl VisCanvas {
+    pub fn new(id: Id) -> Self {
+        Self {
+            id,
+            origin: Origin::TopLeft,
+            min_zoom: 0.0,
+            max_zoom: 10.0,
+            background: None,
+            auto_fit: None,
+            pan_bounds: None,
+            interaction: CanvasInteraction::default(),
+            selection: None,
+            style: CanvasStyle::default(),
+        }
+    }
+
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Smallest allowed canvas scale. Defaults to `0.0`.
+    pub fn min_zoom(mut self, min_zoom: f32) -> Self {
+        self.min_zoom = min_zoom;
+        self
+    }
+
+    /// Largest allowed canvas scale. Defaults to `10.0`.
+    pub fn max_zoom(mut self, max_zoom: f32) -> Self {
+        self.max_zoom = max_zoom;
+        self
+    }
+
+    /// Keeps the middle of the view inside `bounds`, given in canvas coordinates.
+    pub fn pan_bounds(mut self, bounds: Rect) -> Self {
+        self.pan_bounds = Some(bounds);
+        self
+    }
+
+    pub fn interaction(mut self, interaction: CanvasInteraction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    /// Enables selection: clicking a content with an id selects it, shift-click
+    /// toggles it and clicking the background clears the selection. Dragging with
+    /// the primary button from the background selects everything the marquee
+    /// touches (shift adds to the selection). Selected contents are outlined with
+    /// `highlight`.
+    pub fn selection(mut self, highlight: Stroke) -> Self {
+        self.selection = Some(highlight);
+        self
+    }
+
+    pub fn style(mut self, style: CanvasStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Fills the canvas area before any content is painted.
+    pub fn background(mut self, background: Color32) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Frames the contents with `padding` screen pixels on the first frame and
+    /// again whenever their bounding box changes. Pan and zoom by the user are
+    /// kept until then. Moving contents by dragging them does not refit.
+    pub fn auto_fit(mut self, padding: f32) -> Self {
+        self.auto_fit = Some(padding);
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui, contents: &[Content]) -> Result<VisCanvasOutput> {
+        let mut state = VisCanvasState::load(ui.ctx(), self.id);
+        let inner = &mut state.inner_state;
+        inner.origin = self.origin;
+        inner.min_zoom = self.min_zoom;
+        inner.max_zoom = self.max_zoom;
+        inner.pan_bounds = self.pan_bounds;
+        if let Some((scale, center)) = inner.requested_view.take() {
+            // The requested view wins over the first auto-fit.
+            inner.viewport = ui.available_rect_before_wrap();
+            inner.fitted_bounds = contents_bounding_rect(contents);
+            inner.current_scale = inner.clamp_scale(scale);
+            inner.center_on(center);
+        }
+        inner.clamp_view();
+        inner.style = self.style.clone();
+        if let Some(padding) = self.auto_fit {
+            let bounds = contents_bounding_rect(contents);
+            if inner.dragged.is_some() {
+                // Follow the dragged contents without pulling the view along.
+                inner.fitted_bounds = bounds;
+            } else if bounds.is_some() && bounds != inner.fitted_bounds {
+                inner.viewport = ui.available_rect_before_wrap();
+                inner.fitted_bounds = bounds;
+                state.fit_contents(contents, padding);
+            }
+        }
+        let (response, content_responses, drag) = state.show_body(ui, &self, contents)?;
+        state.store(ui.ctx());
+        let selected = if self.selection.is_some() {
+            state.inner_state.selected.clone()
+        } else {
+            Vec::new()
+        };
+        Ok(VisCanvasOutput {
+            response,
+            state,
+            content_responses,
+            selected,
+            drag,
+        })
+    }
+}
+
+/// Affine map from canvas to screen coordinates: `screen = canvas * scale + offset`.
+/// `scale.y` is negative for [`Origin::BottomLeft`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanvasTransform {
+    pub offset: Vec2,
+    pub scale: Vec2,
+}
+
+impl CanvasTransform {
+    pub fn to_screen(&self, pos: Pos2) -> Pos2 {
+        (pos.to_vec2() * self.scale + self.offset).to_pos2()
+    }
+
+    pub fn to_canvas(&self, screen_pos: Pos2) -> Pos2 {
+        ((screen_pos.to_vec2() - self.offset) / self.scale).to_pos2()
+    }
+
+    /// Transforms a displacement; the offset does not apply.
+    pub fn vec_to_screen(&self, vec: Vec2) -> Vec2 {
+        vec * self.scale
+    }
+
+    pub fn vec_to_canvas(&self, screen_vec: Vec2) -> Vec2 {
+        screen_vec / self.scale
+    }
+
+    pub fn rect_to_screen(&self, rect: Rect) -> Rect {
+        Rect::from_two_pos(self.to_screen(rect.min), self.to_screen(rect.max))
+    }
+
+    pub fn rect_to_canvas(&self, screen_rect: Rect) -> Rect {
+        Rect::from_two_pos(
+            self.to_canvas(screen_rect.min),
+            self.to_canvas(screen_rect.max),
+        )
+    }
+}
+
+pub struct VisCanvasState {
+    pub id: Id,
+    inner_state: VisCanvasStateInner,
+}
+
+#[derive(Debug, Clone)]
+pub struct VisCanvasStateInner {
+    origin: Origin,
+    min_zoom: f32,
+    max_zoom: f32,
+    current_scale: f32,
+    shift: Vec2,
+    /// Canvas area in screen coordinates as of the last frame
+    viewport: Rect,
+    /// Content bounds last framed by [`VisCanvas::auto_fit`]
+    fitted_bounds: Option<Rect>,
+    pan_bounds: Option<Rect>,
+    /// Screen pixels per second of the last pan drag, for kinetic panning
+    pan_velocity: Vec2,
+    selected: Vec<Id>,
+    /// Canvas position where the current rubber-band selection started
+    marquee_start: Option<Pos2>,
+    /// Content being dragged and the pointer's canvas offset from its bounds' corner
+    dragged: Option<(Id, Vec2)>,
+    /// Canvas bounds of text contents as laid out on the last frame, by index
+    laid_out: Vec<Option<Rect>>,
+    /// Pointer position while it hovers the canvas this frame, in screen coordinates
+    hover_pos: Option<Pos2>,
+    /// Scale and center requested by [`VisCanvasState::from_query_string`]
+    requested_view: Option<(f32, Pos2)>,
+    style: CanvasStyle,
+}
+
+/// The part of [`VisCanvasStateInner`] kept in egui's persisted memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PersistedView {
+    origin: Origin,
+    min_zoom: f32,
+    max_zoom: f32,
+    current_scale: f32,
+    shift: Vec2,
+    pan_bounds: Option<Rect>,
+}
+
+impl Default for VisCanvasStateInner {
+    fn default() -> Self {
+        Self {
+            current_scale: 1.0,
+            shift: Vec2::ZERO,
+            origin: Origin::TopLeft,
+            min_zoom: 0.0,
+            max_zoom: 10.0,
+            viewport: Rect::ZERO,
+            fitted_bounds: None,
+            pan_bounds: None,
+            pan_velocity: Vec2::ZERO,
+            selected: Vec::new(),
+            marquee_start: None,
+            dragged: None,
+            laid_out: Vec::new(),
+            hover_pos: None,
+            requested_view: None,
+            style: CanvasStyle::default(),
+        }
+    }
+}
+
+impl VisCanvasState {
+    /// Screen positions are in egui coordinates, like [`Response::hover_pos`].
+    /// Returns `None` if the scale is degenerate.
+    pub fn screen_to_canvas(&self, screen_pos: Pos2) -> Option<Pos2> {
+        let pos = self.transform().to_canvas(screen_pos);
+        pos.is_finite().then_some(pos)
+    }
+
+    pub fn canvas_to_screen(&self, pos: Pos2) -> Pos2 {
+        self.transform().to_screen(pos)
+    }
+
+    /// Mapping between canvas and screen coordinates as of the last frame.
+    pub fn transform(&self) -> CanvasTransform {
+        let inner = &self.inner_state;
+        CanvasTransform {
+            offset: inner.viewport.min.to_vec2() + inner.shift,
+            scale: inner.current_scale_vec(),
+        }
+    }
+
+    /// Encodes the view as `scale=..&center_x=..&center_y=..`, e.g. for a page
+    /// URL. The center is the canvas position in the middle of the canvas area,
+    /// so the link shows the same place whatever the size of the widget.
+    pub fn to_query_string(&self) -> String {
+        let state = &self.inner_state;
+        let center = state.view_center();
+        format!(
+            "scale={}&center_x={}&center_y={}",
+            state.current_scale, center.x, center.y
+        )
+    }
+
+    /// Requests the view of canvas `id` encoded by [`Self::to_query_string`].
+    /// It takes effect on the next [`VisCanvas::show`], once the canvas size is
+    /// known, with the scale clamped to the zoom limits of that canvas. A leading
+    /// `?` and unknown keys are ignored. Returns `None` without touching the stored
+    /// view if a value does not parse or the scale is not positive.
+    pub fn from_query_string(ctx: &Context, id: Id, query: &str) -> Option<Self> {
+        let mut state = Self::load(ctx, id);
+        let inner = &mut state.inner_state;
+        let (mut scale, mut center) = (inner.current_scale, inner.view_center());
+        for pair in query.trim_start_matches('?').split('&') {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let parse = || value.parse::<f32>().ok().filter(|v| v.is_finite());
+            match key {
+                "scale" => scale = parse()?,
+                "center_x" => center.x = parse()?,
+                "center_y" => center.y = parse()?,
+                _ => {}
+            }
+        }
+        if scale <= 0.0 {
+            return None;
+        }
+        inner.requested_view = Some((scale, center));
+
+        state.store(ctx);
+        Some(state)
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.inner_state.current_scale
+    }
+
+    /// Screen offset of the canvas origin from the top-left corner of the canvas area.
+    pub fn shift(&self) -> Vec2 {
+        self.inner_state.shift
+    }
+
+    /// Sets the scale, clamped to the canvas zoom limits. Non-positive or NaN
+    /// scales are ignored.
+    pub fn set_scale(&mut self, scale: f32) {
+        let inner = &mut self.inner_state;
+        if scale > 0.0 {
+            inner.current_scale = inner.clamp_scale(scale);
+        }
+    }
+
+    pub fn set_shift(&mut self, shift: Vec2) {
+        self.inner_state.shift = shift;
+    }
+
+    pub fn selected(&self) -> &[Id] {
+        &self.inner_state.selected
+    }
+
+    pub fn set_selected(&mut self, selected: Vec<Id>) {
+        self.inner_state.selected = selected;
+    }
+
+    /// Back to scale 1 with the canvas origin at the top-left corner.
+    pub fn reset_view(&mut self) {
+        self.inner_state.reset_view();
+    }
+
+    /// Pans so that the canvas position `pos` is in the middle of the canvas area.
+    /// Uses the canvas size from the last frame.
+    pub fn center_on(&mut self, pos: Pos2) {
+        self.inner_state.center_on(pos);
+    }
+
+    /// Zooms and pans so that `rect` (in canvas coordinates) fills the canvas area
+    /// while keeping its aspect ratio.
+    pub fn zoom_to_rect(&mut self, rect: Rect) {
+        self.fit_rect(rect, 0.0);
+    }
+
+    /// Zooms and pans so that every content with a known extent is visible, leaving
+    /// `padding` screen pixels around them. Does nothing if no extent is known.
+    pub fn fit_contents(&mut self, contents: &[Content], padding: f32) {
+        let bounds = (0..contents.len())
+            .filter_map(|i| self.inner_state.content_bounds(contents, i))
+            .reduce(|a, b| a.union(b));
+        if let Some(rect) = bounds {
+            self.fit_rect(rect, padding);
+        }
+    }
+
+    fn fit_rect(&mut self, rect: Rect, padding: f32) {
+        if !rect.is_finite() {
+            return;
+        }
+        let available =
+            (self.inner_state.viewport.size() - Vec2::splat(2.0 * padding)).max(Vec2::ZERO);
+        let scale = (available / rect.size()).min_elem();
+        if scale.is_nan() {
+            return;
+        }
+        self.set_scale(scale);
+        self.center_on(rect.center());
+    }
+
+    /// Reads the state of canvas `id` as of the last frame. Changes made with the
+    /// camera methods take effect once written back with [`Self::store`].
+    /// Only the view outlives the session; selection, drags and layout start
+    /// afresh. A corrupt or out-of-bounds stored state is replaced by the default view.
+    pub fn load(ctx: &Context, id: Id) -> Self {
+        let inner_state = ctx.data_mut(|data| {
+            data.get_temp::<VisCanvasStateInner>(id)
+                .or_else(|| {
+                    data.get_persisted::<PersistedView>(id)
+                        .map(VisCanvasStateInner::from_view)
+                })
+                .filter(VisCanvasStateInner::is_valid)
+                .unwrap_or_default()
+        });
+        Self { id, inner_state }
+    }
+
+    pub fn store(&self, ctx: &Context) {
+        let view = self.inner_state.view();
+        ctx.data_mut(|data| {
+            if data.get_persisted::<PersistedView>(self.id) != Some(view) {
+                data.insert_persisted(self.id, view);
+            }
+            data.insert_temp(self.id, self.inner_state.clone());
+        });
+    }
+
+    pub(crate) fn show_body(
+        &mut self,
+        ui: &mut Ui,
+        canvas: &VisCanvas,
+        contents: &[Content],
+    ) -> Result<(Response, Vec<ContentResponse>, Option<DragEvent>)> {
+        let (response, content_responses, drag) = ui
+            .centered_and_justified(|ui| {
+                let (response, mut painter) =
+                    ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+                self.inner_state.hover_pos = response.hover_pos();
+                if let Some(background) = canvas.background {
+                    painter.rect_filled(response.rect, 0.0, background);
+                }
+                for content in iter_ordered(contents) {
+                    if !content.is_visible_at_scale(self.inner_state.current_scale) {
+                        continue;
+                    }
+                    let opacity = painter.opacity();
+                    if let Some(animation) = content.animation() {
+                        let time = ui.input(|input| input.time);
+                        painter.set_opacity(opacity * animation.opacity(time));
+                        // Keep animating only while animated contents are shown.
+                        ui.ctx().request_repaint();
+                    }
+                    match content {
+                        Content::Rectangle(rect) => {
+                            rect.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Image(image) => {
+                            image.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::RawImage(raw_image) => {
+                            raw_image.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::MultiChannelImage(image) => {
+                            image.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::DepthImage(depth_image) => {
+                            depth_image.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Segment(segment) => {
+                            segment.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::PiecewiseSegment(piecewise_segment) => {
+                            piecewise_segment.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::TextBox(text_box) => {
+                            text_box.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Callout(callout) => {
+                            callout.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Badge(badge) => {
+                            badge.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Gauge(gauge) => {
+                            gauge.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::MiniTable(mini_table) => {
+                            mini_table.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Sparkline(sparkline) => {
+                            sparkline.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Custom(item) => {
+                            item.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Polygon(polygon) => {
+                            polygon.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Ellipse(ellipse) => {
+                            ellipse.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::CubicBezier(bezier) => {
+                            bezier.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::QuadraticBezier(bezier) => {
+                            bezier.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Arc(arc) => {
+                            arc.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Points(points) => {
+                            points.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                        Content::Circle(circle) => {
+                            circle.show(ui, &mut painter, &self.inner_state)?;
+                        }
+                    }
+                    painter.set_opacity(opacity);
+                }
+                let inner = &self.inner_state;
+                self.inner_state.laid_out = contents
+                    .iter()
+                    .map(|content| {
+                        let rect = content.screen_rect(&painter, inner)?;
+                        let min = inner.to_canvas_pos(&painter, rect.min);
+                        let max = inner.to_canvas_pos(&painter, rect.max);
+                        Some(Rect::from_two_pos(min, max))
+                    })
+                    .collect();
+                let content_responses = self.pick(&painter, &response, contents);
+                let press_origin = ui.input(|input| input.pointer.press_origin());
+                let drag = self.update_drag(
+                    &painter,
+                    &response,
+                    &content_responses,
+                    contents,
+                    press_origin,
+                );
+                if let Some(highlight) = canvas.selection {
+                    let toggle = ui.input(|input| input.modifiers.shift);
+                    self.inner_state
+                        .update_selection(&response, &content_responses, toggle);
+                    // A primary-button pan takes precedence over the marquee.
+                    let on_background = canvas.interaction.pan_button != PointerButton::Primary
+                        && content_responses
+                            .iter()
+                            .all(|content_response| !content_response.hovered);
+                    if let Some(marquee) =
+                        self.update_marquee(&painter, &response, on_background, press_origin)
+                    {
+                        self.inner_state.select_in_rect(marquee, contents, toggle);
+                    }
+                    self.paint_selection(&painter, contents, highlight);
+                }
+                Ok::<_, VisCanvasError>((response, content_responses, drag))
+            })
+            .inner?;
+
+        let interaction = &canvas.interaction;
+        let background_double_clicked = response.double_clicked()
+            && !content_responses
+                .iter()
+                .any(|content_response| content_response.hovered);
+        if background_double_clicked {
+            match interaction.double_click {
+                DoubleClickAction::None => {}
+                DoubleClickAction::ResetView => self.reset_view(),
+                DoubleClickAction::FitContents { padding } => {
+                    self.inner_state.viewport = response.rect;
+                    self.fit_contents(contents, padding);
+                }
+            }
+        }
+        let state = &mut self.inner_state;
+        // Dragging a content does not pan the view.
+        if response.dragged_by(interaction.pan_button) && drag.is_none() {
+            state.shift += response.drag_delta();
+            state.pan_velocity = ui.input(|input| input.pointer.velocity());
+        } else if drag.is_some() || response.is_pointer_button_down_on() {
+            // A press on the canvas or a content drag stops the glide.
+            state.pan_velocity = Vec2::ZERO;
+        } else if let Some(friction) = interaction.kinetic_friction {
+            // Keep gliding after the drag is released.
+            if state.pan_velocity.length() > 1.0 {
+                let dt = ui.input(|input| input.stable_dt);
+                state.shift += state.pan_velocity * dt;
+                state.pan_velocity *= (-friction * dt).exp();
+                ui.ctx().request_repaint();
+            } else {
+                state.pan_velocity = Vec2::ZERO;
+            }
+        }
+
+        // Pinch gestures zoom about the centroid of the touches.
+        let gesture_pos = ui
+            .input(|input| input.multi_touch())
+            .map(|touch| touch.center_pos)
+            .filter(|pos| response.rect.contains(*pos))
+            .or(response.hover_pos());
+        if let Some(hover_pos) = gesture_pos {
+            let hover_pos = hover_pos - response.rect.min;
+            ui.input(|input| {
+                // スクロール関係
+                // egui folds ctrl + wheel into zoom_delta and leaves it out of
+                // smooth_scroll_delta.
+                let ctrl = input.modifiers.ctrl || input.modifiers.command;
+                let mut zoom_delta = if interaction.ctrl_to_zoom || !ctrl {
+                    input.zoom_delta()
+                } else {
+                    1.0
+                };
+                {
+                    // Two-finger drag on touch screens
+                    state.shift += input.translation_delta();
+                    let delta = input.smooth_scroll_delta * interaction.scroll_speed;
+                    match interaction.scroll {
+                        ScrollAction::Pan => state.shift += delta,
+                        ScrollAction::Zoom => zoom_delta *= (delta.y / 200.0).exp(),
+                    }
+                }
+                // ズーム関係
+                {
+                    // https://chat.openai.com/share/e/c46c2795-a9e4-4f23-b04c-fa0b0e8ab818
+                    let scale = zoom_delta.powf(interaction.zoom_speed);
+                    state.zoom_about(scale, hover_pos);
+                }
+            });
+        }
+
+        if interaction.keyboard && (response.clicked() || response.drag_started()) {
+            response.request_focus();
+        }
+        if interaction.keyboard && response.has_focus() {
+            // Keep arrow keys from moving focus to other widgets.
+            ui.memory_mut(|memory| {
+                memory.set_focus_lock_filter(
+                    response.id,
+                    EventFilter {
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        ..Default::default()
+                    },
+                );
+            });
+            let center = response.rect.size() / 2.0;
+            let step = interaction.key_pan_step;
+            ui.input(|input| {
+                if input.key_pressed(Key::ArrowLeft) {
+                    state.shift.x += step;
+                }
+                if input.key_pressed(Key::ArrowRight) {
+                    state.shift.x -= step;
+                }
+                if input.key_pressed(Key::ArrowUp) {
+                    state.shift.y += step;
+                }
+                if input.key_pressed(Key::ArrowDown) {
+                    state.shift.y -= step;
+                }
+                if input.key_pressed(Key::Plus) || input.key_pressed(Key::Equals) {
+                    state.zoom_about(interaction.key_zoom_step, center);
+                }
+                if input.key_pressed(Key::Minus) {
+                    state.zoom_about(1.0 / interaction.key_zoom_step, center);
+                }
+                if input.key_pressed(Key::Num0) || input.key_pressed(Key::Home) {
+                    state.reset_view();
+                }
+            });
+        }
+
+        state.viewport = response.rect;
+        state.clamp_view();
+
+        Ok((response, content_responses, drag))
+    }
+
+    /// Tracks a primary-button drag that starts on a draggable content.
+    fn update_drag(
+        &mut self,
+        painter: &Painter,
+        response: &Response,
+        content_responses: &[ContentResponse],
+        contents: &[Content],
+        press_origin: Option<Pos2>,
+    ) -> Option<DragEvent> {
+        let inner = &mut self.inner_state;
+        if response.drag_started_by(PointerButton::Primary) {
+            let grabbed = content_responses
+                .iter()
+                .find(|content_response| content_response.hovered)
+                .and_then(|content_response| {
+                    contents
+                        .iter()
+                        .find(|content| content.id() == Some(content_response.id))
+                        .filter(|content| content.is_draggable())
+                        .map(|content| (content_response.id, content))
+                });
+            inner.dragged = grabbed
+                .zip(press_origin)
+                .map(|((id, content), press_origin)| {
+                    let pointer = inner.to_canvas_pos(painter, press_origin);
+                    let corner = content.bounding_rect().map_or(pointer, |rect| rect.min);
+                    (id, pointer - corner)
+                });
+        }
+        let (id, offset) = inner.dragged?;
+        let finished = response.drag_stopped();
+        if finished {
+            inner.dragged = None;
+        }
+        let pointer = inner.to_canvas_pos(painter, response.interact_pointer_pos()?);
+        Some(DragEvent {
+            id,
+            new_position: pointer - offset,
+            delta: response.drag_delta() / inner.current_scale_vec(),
+            finished,
+        })
+    }
+
+    /// Tracks a primary-button drag that starts on the background and paints its
+    /// marquee. Returns the marquee in canvas coordinates once the drag ends.
+    fn update_marquee(
+        &mut self,
+        painter: &Painter,
+        response: &Response,
+        on_background: bool,
+        press_origin: Option<Pos2>,
+    ) -> Option<Rect> {
+        let inner = &mut self.inner_state;
+        if response.drag_started_by(PointerButton::Primary) && on_background {
+            inner.marquee_start = press_origin.map(|pos| inner.to_canvas_pos(painter, pos));
+        }
+        let start = inner.marquee_start?;
+        let current = response.interact_pointer_pos()?;
+        let start = inner.to_screen_pos(painter, start);
+        let screen_rect = Rect::from_two_pos(start, current);
+        if response.drag_stopped() {
+            inner.marquee_start = None;
+            let min = inner.to_canvas_pos(painter, screen_rect.min);
+            let max = inner.to_canvas_pos(painter, screen_rect.max);
+            return Some(Rect::from_two_pos(min, max));
+        }
+        let style = painter.ctx().style();
+        let selection = &style.visuals.selection;
+        painter.rect(
+            screen_rect,
+            0.0,
+            selection.bg_fill.gamma_multiply(0.3),
+            selection.stroke,
+        );
+        None
+    }
+
+    fn paint_selection(&self, painter: &Painter, contents: &[Content], highlight: Stroke) {
+        let transform = CanvasTransform {
+            offset: painter.clip_rect().min.to_vec2() + self.inner_state.shift,
+            scale: self.inner_state.current_scale_vec(),
+        };
+        for (i, content) in contents.iter().enumerate() {
+            let selected = content
+                .id()
+                .is_some_and(|id| self.inner_state.selected.contains(&id));
+            if !selected {
+                continue;
+            }
+            if let Some(rect) = self.inner_state.content_bounds(contents, i) {
+                let rect = transform.rect_to_screen(rect).expand(highlight.width + 1.0);
+                painter.rect_stroke(rect, 2.0, highlight);
+            }
+        }
+    }
+
+    fn pick(
+        &self,
+        painter: &Painter,
+        response: &Response,
+        contents: &[Content],
+    ) -> Vec<ContentResponse> {
+        let state = &self.inner_state;
+        let tolerance = HIT_TOLERANCE / state.current_scale.max(f32::EPSILON);
+        let hovered = response.hover_pos().and_then(|hover_pos| {
+            let pos = state.to_canvas_pos(painter, hover_pos);
+            contents
+                .iter()
+                .enumerate()
+                .filter(|(i, content)| {
+                    content.id().is_some()
+                        && content.is_visible_at_scale(state.current_scale)
+                        && state.hit_test(contents, *i, pos, tolerance)
+                })
+                // Drawn last means on top: highest z-index, then latest in the slice.
+                .max_by_key(|(i, content)| (content.z_index(), *i))
+                .map(|(i, _)| i)
+        });
+
+        contents
+            .iter()
+            .enumerate()
+            .filter_map(|(i, content)| {
+                let id = content.id()?;
+                let hovered = hovered == Some(i);
+                Some(ContentResponse {
+                    id,
+                    hovered,
+                    clicked: hovered && response.clicked(),
+                    double_clicked: hovered && response.double_clicked(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl VisCanvasStateInner {
+    /// Converts a canvas position to a screen position inside `painter`'s clip rect.
+    pub fn to_screen_pos(&self, painter: &Painter, pos: Pos2) -> Pos2 {
+        painter.clip_rect().min + (pos.to_vec2() * self.current_scale_vec() + self.shift)
+    }
+
+    /// Inverse of [`Self::to_screen_pos`].
+    pub fn to_canvas_pos(&self, painter: &Painter, screen_pos: Pos2) -> Pos2 {
+        ((screen_pos - painter.clip_rect().min - self.shift) / self.current_scale_vec()).to_pos2()
+    }
+
+    pub fn style(&self) -> &CanvasStyle {
+        &self.style
+    }
+
+    pub fn current_scale_vec(&self) -> Vec2 {
+        match self.origin {
+            Origin::TopLeft => Vec2::new(self.current_scale, self.current_scale),
+            Origin::BottomLeft => Vec2::new(self.current_scale, -self.current_scale),
+        }
+    }
+
+    fn view(&self) -> PersistedView {
+        PersistedView {
+            origin: self.origin,
+            min_zoom: self.min_zoom,
+            max_zoom: self.max_zoom,
+            current_scale: self.current_scale,
+            shift: self.shift,
+            pan_bounds: self.pan_bounds,
+        }
+    }
+
+    fn from_view(view: PersistedView) -> Self {
+        Self {
+            origin: view.origin,
+            min_zoom: view.min_zoom,
+            max_zoom: view.max_zoom,
+            current_scale: view.current_scale,
+            shift: view.shift,
+            pan_bounds: view.pan_bounds,
+            ..Default::default()
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        0.0 < self.current_scale
+            && self.min_zoom <= self.current_scale
+            && self.current_scale <= self.max_zoom
+            && self.shift.is_finite()
+    }
+
+    /// Bounds recorded when `contents[index]` was laid out on the last frame, if any.
+    fn laid_out_rect(&self, contents: &[Content], index: usize) -> Option<Rect> {
+        if self.laid_out.len() != contents.len() {
+            return None;
+        }
+        self.laid_out[index]
+    }
+
+    /// Bounds of `contents[index]`, preferring its laid-out extent.
+    fn content_bounds(&self, contents: &[Content], index: usize) -> Option<Rect> {
+        self.laid_out_rect(contents, index)
+            .or_else(|| contents[index].bounding_rect())
+    }
+
+    fn hit_test(&self, contents: &[Content], index: usize, pos: Pos2, tolerance: f32) -> bool {
+        match self.laid_out_rect(contents, index) {
+            Some(rect) => rect.expand(tolerance).contains(pos),
+            None => contents[index].hit_test(pos, tolerance),
+        }
+    }
+
+    /// Selects every content with an id whose bounds intersect `rect`, adding to
+    /// the selection if `extend` is set.
+    fn select_in_rect(&mut self, rect: Rect, contents: &[Content], extend: bool) {
+        if !extend {
+            self.selected.clear();
+        }
+        for (i, content) in contents.iter().enumerate() {
+            let (Some(id), Some(bounds)) = (content.id(), self.content_bounds(contents, i)) else {
+                continue;
+            };
+            if bounds.intersects(rect) && !self.selected.contains(&id) {
+                self.selected.push(id);
+            }
+        }
+    }
+
+    fn update_selection(
+        &mut self,
+        response: &Response,
+        content_responses: &[ContentResponse],
+        toggle: bool,
+    ) {
+        if !response.clicked() {
+            return;
+        }
+        let clicked = content_responses
+            .iter()
+            .find(|content_response| content_response.clicked)
+            .map(|content_response| content_response.id);
+        match (clicked, toggle) {
+            (Some(id), true) => {
+                if let Some(index) = self.selected.iter().position(|&selected| selected == id) {
+                    self.selected.remove(index);
+                } else {
+                    self.selected.push(id);
+                }
+            }
+            (Some(id), false) => self.selected = vec![id],
+            (None, true) => {}
+            (None, false) => self.selected.clear(),
+        }
+    }
+
+    fn clamp_scale(&self, scale: f32) -> f32 {
+        scale.max(self.min_zoom).min(self.max_zoom)
+    }
+
+    /// Scales by `factor`, limited to the zoom range, keeping the point `pos`,
+    /// relative to the canvas area, in place.
+    fn zoom_about(&mut self, factor: f32, pos: Vec2) {
+        let scale = self.clamp_scale(self.current_scale * factor);
+        if scale.is_nan() || scale <= 0.0 {
+            return;
+        }
+        let factor = scale / self.current_scale;
+        self.current_scale = scale;
+        self.shift = self.shift * factor + pos * (1.0 - factor);
+    }
+
+    /// Brings the scale back into the zoom limits, about the middle of the view,
+    /// and the view into `pan_bounds`.
+    fn clamp_view(&mut self) {
+        if !self.shift.is_finite() {
+            self.shift = Vec2::ZERO;
+        }
+        if self.current_scale > 0.0 && self.current_scale.is_finite() {
+            // A unit factor only applies the zoom limits.
+            self.zoom_about(1.0, self.viewport.size() / 2.0);
+        } else {
+            self.current_scale = self.clamp_scale(1.0);
+        }
+        self.clamp_to_pan_bounds();
+    }
+
+    fn reset_view(&mut self) {
+        self.current_scale = 1.0;
+        self.shift = Vec2::ZERO;
+    }
+
+    /// Pans back so that the canvas position in the middle of the view stays
+    /// inside `pan_bounds`.
+    fn clamp_to_pan_bounds(&mut self) {
+        let Some(pan_bounds) = self.pan_bounds else {
+            return;
+        };
+        let clamped = self.view_center().clamp(pan_bounds.min, pan_bounds.max);
+        self.center_on(clamped);
+    }
+
+    /// Canvas position in the middle of the canvas area.
+    fn view_center(&self) -> Pos2 {
+        ((self.viewport.size() / 2.0 - self.shift) / self.current_scale_vec()).to_pos2()
+    }
+
+    fn center_on(&mut self, pos: Pos2) {
+        self.shift = self.viewport.size() / 2.0 - pos.to_vec2() * self.current_scale_vec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_level_bounds() {
+        let window_level = WindowLevel::from_min_max(100.0, 300.0);
+        assert_eq!(window_level.window, 200.0);
+        assert_eq!(window_level.level, 200.0);
+        assert_eq!(window_level.min(), 100.0);
+        assert_eq!(window_level.max(), 300.0);
+    }
+
+    #[test]
+    fn window_level_normalizes_and_clamps() {
+        let window_level = WindowLevel::new(200.0, 200.0);
+        assert_eq!(window_level.normalize(100.0), 0.0);
+        assert_eq!(window_level.normalize(200.0), 0.5);
+        assert_eq!(window_level.normalize(300.0), 1.0);
+        assert_eq!(window_level.normalize(-1000.0), 0.0);
+        assert_eq!(window_level.normalize(1000.0), 1.0);
+    }
+
+    #[test]
+    fn empty_window_thresholds_at_level() {
+        for window in [0.0, -10.0] {
+            let window_level = WindowLevel::new(window, 50.0);
+            assert_eq!(window_level.normalize(49.9), 0.0);
+            assert_eq!(window_level.normalize(50.0), 1.0);
+            assert_eq!(window_level.normalize(51.0), 1.0);
+        }
+    }
+
+    /// Runs one frame with the canvas filling a window of `size`.
+    fn show_frame(ctx: &Context, size: Vec2, canvas: VisCanvas) -> VisCanvasOutput {
+        let input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, size)),
+            ..Default::default()
+        };
+        let mut canvas = Some(canvas);
+        let mut output = None;
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| {
+                    if let Some(canvas) = canvas.take() {
+                        output = Some(canvas.show(ui, &[]).unwrap());
+                    }
+                });
+        });
+        output.unwrap()
+    }
+
+    fn shown_center(output: &VisCanvasOutput) -> Pos2 {
+        output
+            .state
+            .screen_to_canvas(output.response.rect.center())
+            .unwrap()
+    }
+
+    #[test]
+    fn query_string_round_trip_across_window_sizes() {
+        let id = Id::new("canvas");
+        let ctx = Context::default();
+        let mut state = show_frame(&ctx, Vec2::new(400.0, 300.0), VisCanvas::new(id)).state;
+        state.set_scale(2.5);
+        state.center_on(Pos2::new(10.0, 20.0));
+        let query = state.to_query_string();
+
+        let other = Context::default();
+        assert!(VisCanvasState::from_query_string(&other, id, &format!("?{query}")).is_some());
+        let output = show_frame(&other, Vec2::new(800.0, 600.0), VisCanvas::new(id));
+        assert_eq!(output.state.scale(), 2.5);
+        assert!(shown_center(&output).distance(Pos2::new(10.0, 20.0)) < 1e-3);
+    }
+
+    #[test]
+    fn query_string_scale_is_clamped_to_the_canvas_zoom_limits() {
+        let ctx = Context::default();
+        let id = Id::new("canvas");
+        let size = Vec2::new(400.0, 300.0);
+        assert!(VisCanvasState::from_query_string(&ctx, id, "scale=100").is_some());
+        let output = show_frame(&ctx, size, VisCanvas::new(id).max_zoom(200.0));
+        assert_eq!(output.state.scale(), 100.0);
+
+        assert!(VisCanvasState::from_query_string(&ctx, id, "scale=100").is_some());
+        let output = show_frame(&ctx, size, VisCanvas::new(id).max_zoom(20.0));
+        assert_eq!(output.state.scale(), 20.0);
+    }
+
+    #[test]
+    fn query_string_ignores_unknown_keys_and_bare_words() {
+        let ctx = Context::default();
+        let id = Id::new("canvas");
+        let query = "foo=bar&scale=3&flag&center_x=5&center_y=7";
+        assert!(VisCanvasState::from_query_string(&ctx, id, query).is_some());
+        let output = show_frame(&ctx, Vec2::new(400.0, 300.0), VisCanvas::new(id));
+        assert_eq!(output.state.scale(), 3.0);
+        assert!(shown_center(&output).distance(Pos2::new(5.0, 7.0)) < 1e-3);
+    }
+
+    #[test]
+    fn malformed_query_string_leaves_stored_view_untouched() {
+        let ctx = Context::default();
+        let id = Id::new("canvas");
+        for query in [
+            "scale=abc",
+            "scale=2&center_x=",
+            "center_y=1e",
+            "scale=NaN",
+            "scale=inf",
+            "scale=0",
+            "scale=-1",
+            "center_x=NaN",
+            "center_y=-inf",
+        ] {
+            assert!(
+                VisCanvasState::from_query_string(&ctx, id, query).is_none(),
+                "{query}"
+            );
+            let output = show_frame(&ctx, Vec2::new(400.0, 300.0), VisCanvas::new(id));
+            assert_eq!(output.state.scale(), 1.0, "{query}");
+            assert_eq!(output.state.shift(), Vec2::ZERO, "{query}");
+        }
+    }
+
+    #[test]
+    fn only_the_view_outlives_the_session() {
+        let ctx = Context::default();
+        let id = Id::new("canvas");
+        let mut state = VisCanvasState::load(&ctx, id);
+        state.set_scale(2.0);
+        state.set_shift(Vec2::new(3.0, 4.0));
+        state.set_selected(vec![Id::new("box")]);
+        state.store(&ctx);
+        assert_eq!(VisCanvasState::load(&ctx, id).selected(), [Id::new("box")]);
+
+        // A new session only has the persisted memory.
+        ctx.data_mut(|data| data.remove::<VisCanvasStateInner>(id));
+        let restored = VisCanvasState::load(&ctx, id);
+        assert_eq!(restored.scale(), 2.0);
+        assert_eq!(restored.shift(), Vec2::new(3.0, 4.0));
+        assert!(restored.selected().is_empty());
+    }
+
+    #[test]
+    fn chevrons_are_only_laid_out_inside_the_clip_rect() {
+        let chevrons = Chevrons::new(Thickness::Absolute(10.0));
+        let stroke = Stroke::new(1.0, Color32::BLACK);
+        let points = [Pos2::new(0.0, 0.0), Pos2::new(1.0e6, 0.0)];
+        let visible = Rect::from_min_max(Pos2::new(500_000.0, -10.0), Pos2::new(500_100.0, 10.0));
+        // Chevrons every 10 px from 5 px, within the clip rect grown by their size
+        assert_eq!(chevrons.shapes(&points, 1.0, stroke, visible).len(), 12);
+        let off_segment = visible.translate(Vec2::new(0.0, 100.0));
+        assert!(chevrons
+            .shapes(&points, 1.0, stroke, off_segment)
+            .is_empty());
+    }
+
+    #[test]
+    fn chevron_spacing_continues_across_segments() {
+        let chevrons = Chevrons::new(Thickness::Absolute(10.0));
+        let stroke = Stroke::new(1.0, Color32::BLACK);
+        let points = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(12.0, 0.0),
+            Pos2::new(12.0, 28.0),
+        ];
+        let everywhere = Rect::from_min_max(Pos2::splat(-100.0), Pos2::splat(100.0));
+        // At 5, 15, 25 and 35 px along the 40 px path
+        assert_eq!(chevrons.shapes(&points, 1.0, stroke, everywhere).len(), 4);
+    }
+
+    fn triangulated_area(vertices: &[Pos2], triangles: &[[usize; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (vertices[a], vertices[b], vertices[c]);
+                cross(b - a, c - a).abs() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn triangulate_convex_polygon() {
+        let square = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 0.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(0.0, 1.0),
+        ];
+        let triangles = triangulate(&square);
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangulated_area(&square, &triangles), 1.0);
+    }
+
+    #[test]
+    fn triangulate_concave_polygon_in_either_winding() {
+        let mut l_shape = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(2.0, 0.0),
+            Pos2::new(2.0, 1.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(1.0, 2.0),
+            Pos2::new(0.0, 2.0),
+        ];
+        for _ in 0..2 {
+            let triangles = triangulate(&l_shape);
+            assert_eq!(triangles.len(), 4);
+            assert_eq!(triangulated_area(&l_shape, &triangles), 3.0);
+            l_shape.reverse();
+        }
+    }
+
+    #[test]
+    fn triangulate_polygon_with_collinear_vertex() {
+        let vertices = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 0.0),
+            Pos2::new(2.0, 0.0),
+            Pos2::new(2.0, 2.0),
+            Pos2::new(0.0, 2.0),
+        ];
+        let triangles = triangulate(&vertices);
+        assert_eq!(triangles.len(), 3);
+        assert_eq!(triangulated_area(&vertices, &triangles), 4.0);
+    }
+
+    #[test]
+    fn triangulate_fully_collinear_polygon_without_panicking() {
+        let vertices: Vec<Pos2> = (0..4).map(|i| Pos2::new(i as f32, i as f32)).collect();
+        let triangles = triangulate(&vertices);
+        assert_eq!(triangles.len(), 2);
+        assert!(triangles.iter().flatten().all(|&i| i < vertices.len()));
+        assert_eq!(triangulated_area(&vertices, &triangles), 0.0);
+    }
+
+    #[test]
+    fn triangulate_needs_three_vertices() {
+        assert!(triangulate(&[]).is_empty());
+        assert!(triangulate(&[Pos2::ZERO, Pos2::new(1.0, 0.0)]).is_empty());
     }
 }
@@ -0,0 +1,212 @@
+use crate::{Content, Rectangle};
+use egui::{Color32, Pos2, Rect, Vec2};
+
+const TRUE_POSITIVE_COLOR: Color32 = Color32::from_rgb(0, 200, 0);
+const FALSE_POSITIVE_COLOR: Color32 = Color32::from_rgb(230, 0, 0);
+const FALSE_NEGATIVE_COLOR: Color32 = Color32::from_rgb(255, 150, 0);
+
+/// A prediction matched to a ground truth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Match {
+    /// Index into the predictions slice
+    pub prediction: usize,
+    /// Index into the ground truths slice
+    pub ground_truth: usize,
+    pub iou: f32,
+}
+
+/// Result of matching predictions against ground truths.
+#[derive(Debug, Clone, Default)]
+pub struct MatchResult {
+    /// True positives
+    pub matches: Vec<Match>,
+    /// Predictions without a matching ground truth
+    pub false_positives: Vec<usize>,
+    /// Ground truths without a matching prediction
+    pub false_negatives: Vec<usize>,
+}
+
+pub fn iou(a: Rect, b: Rect) -> f32 {
+    let intersection = a.intersect(b);
+    if !intersection.is_positive() {
+        return 0.0;
+    }
+    let intersection_area = intersection.area();
+    let union_area = a.area() + b.area() - intersection_area;
+    if union_area <= 0.0 {
+        return 0.0;
+    }
+    intersection_area / union_area
+}
+
+/// Greedily matches predictions to ground truths in descending IoU order.
+/// Pairs below `iou_threshold` are never matched.
+pub fn match_rectangles(
+    predictions: &[Rectangle],
+    ground_truths: &[Rectangle],
+    iou_threshold: f32,
+) -> MatchResult {
+    let mut candidates = Vec::new();
+    for (i, prediction) in predictions.iter().enumerate() {
+        for (j, ground_truth) in ground_truths.iter().enumerate() {
+            let iou = iou(prediction.bounding_rect(), ground_truth.bounding_rect());
+            if iou > 0.0 && iou >= iou_threshold {
+                candidates.push(Match {
+                    prediction: i,
+                    ground_truth: j,
+                    iou,
+                });
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.iou.total_cmp(&a.iou));
+
+    let mut prediction_used = vec![false; predictions.len()];
+    let mut ground_truth_used = vec![false; ground_truths.len()];
+    let mut matches = Vec::new();
+    for candidate in candidates {
+        if prediction_used[candidate.prediction] || ground_truth_used[candidate.ground_truth] {
+            continue;
+        }
+        prediction_used[candidate.prediction] = true;
+        ground_truth_used[candidate.ground_truth] = true;
+        matches.push(candidate);
+    }
+
+    MatchResult {
+        matches,
+        false_positives: (0..predictions.len())
+            .filter(|&i| !prediction_used[i])
+            .collect(),
+        false_negatives: (0..ground_truths.len())
+            .filter(|&j| !ground_truth_used[j])
+            .collect(),
+    }
+}
+
+impl MatchResult {
+    /// Builds an overlay where true positives are green and labeled with their IoU,
+    /// false positives are red and false negatives are orange.
+    pub fn to_contents(
+        &self,
+        predictions: &[Rectangle],
+        ground_truths: &[Rectangle],
+        stroke_thickness: f32,
+    ) -> Vec<Content> {
+        let overlay = |rect: &Rectangle, color: Color32| {
+            Rectangle::new()
+                .with_position(Pos2::new(rect.x, rect.y))
+                .with_size(Vec2::new(rect.width, rect.height))
+                .with_stroke_color(color)
+                .with_stroke_thickness(stroke_thickness)
+        };
+
+        let mut contents = Vec::new();
+        for m in &self.matches {
+            contents.push(
+                overlay(
+                    &ground_truths[m.ground_truth],
+                    TRUE_POSITIVE_COLOR.gamma_multiply(0.5),
+                )
+                .into(),
+            );
+            contents.push(
+                overlay(&predictions[m.prediction], TRUE_POSITIVE_COLOR)
+                    .with_label(format!("IoU {:.2}", m.iou))
+                    .into(),
+            );
+        }
+        for &i in &self.false_positives {
+            contents.push(overlay(&predictions[i], FALSE_POSITIVE_COLOR).into());
+        }
+        for &j in &self.false_negatives {
+            contents.push(overlay(&ground_truths[j], FALSE_NEGATIVE_COLOR).into());
+        }
+        contents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rectangle {
+        Rectangle::new()
+            .with_position(Pos2::new(x, y))
+            .with_size(Vec2::new(width, height))
+    }
+
+    fn bounds(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect::from_min_size(Pos2::new(x, y), Vec2::new(width, height))
+    }
+
+    #[test]
+    fn iou_of_identical_rects_is_one() {
+        let a = bounds(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(iou(a, a), 1.0);
+    }
+
+    #[test]
+    fn iou_of_partial_overlap() {
+        let a = bounds(0.0, 0.0, 10.0, 10.0);
+        let b = bounds(5.0, 0.0, 10.0, 10.0);
+        assert!((iou(a, b) - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn iou_of_disjoint_or_touching_rects_is_zero() {
+        let a = bounds(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(iou(a, bounds(20.0, 20.0, 5.0, 5.0)), 0.0);
+        assert_eq!(iou(a, bounds(10.0, 0.0, 10.0, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn iou_of_degenerate_rects_is_zero() {
+        let line = bounds(0.0, 0.0, 10.0, 0.0);
+        assert_eq!(iou(line, line), 0.0);
+        assert_eq!(iou(line, bounds(0.0, 0.0, 10.0, 10.0)), 0.0);
+        let point = bounds(5.0, 5.0, 0.0, 0.0);
+        assert_eq!(iou(point, point), 0.0);
+        assert_eq!(iou(Rect::NOTHING, Rect::NOTHING), 0.0);
+    }
+
+    #[test]
+    fn matches_greedily_by_descending_iou() {
+        let predictions = [rect(4.0, 0.0, 10.0, 10.0), rect(0.0, 0.0, 10.0, 10.0)];
+        let ground_truths = [rect(1.0, 0.0, 10.0, 10.0)];
+        let result = match_rectangles(&predictions, &ground_truths, 0.5);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].prediction, 1);
+        assert_eq!(result.matches[0].ground_truth, 0);
+        assert_eq!(result.false_positives, vec![0]);
+        assert!(result.false_negatives.is_empty());
+    }
+
+    #[test]
+    fn pairs_below_threshold_are_not_matched() {
+        let predictions = [rect(0.0, 0.0, 10.0, 10.0)];
+        let ground_truths = [rect(5.0, 0.0, 10.0, 10.0)];
+        let result = match_rectangles(&predictions, &ground_truths, 0.5);
+        assert!(result.matches.is_empty());
+        assert_eq!(result.false_positives, vec![0]);
+        assert_eq!(result.false_negatives, vec![0]);
+    }
+
+    #[test]
+    fn degenerate_rects_never_match() {
+        let predictions = [rect(0.0, 0.0, 0.0, 0.0)];
+        let ground_truths = [rect(0.0, 0.0, 0.0, 0.0)];
+        let result = match_rectangles(&predictions, &ground_truths, 0.0);
+        assert!(result.matches.is_empty());
+        assert_eq!(result.false_positives, vec![0]);
+        assert_eq!(result.false_negatives, vec![0]);
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let result = match_rectangles(&[], &[rect(0.0, 0.0, 1.0, 1.0)], 0.5);
+        assert!(result.matches.is_empty());
+        assert!(result.false_positives.is_empty());
+        assert_eq!(result.false_negatives, vec![0]);
+    }
+}
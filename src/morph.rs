@@ -0,0 +1,313 @@
+use crate::{
+    ramp_color, Circle, Content, PiecewiseSegment, Polygon, Rectangle, Segment, SegmentData,
+};
+use egui::{Color32, Pos2, Stroke};
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_pos(a: Pos2, b: Pos2, t: f32) -> Pos2 {
+    a + (b - a) * t
+}
+
+fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    ramp_color(&[a, b], t)
+}
+
+fn lerp_stroke(a: Stroke, b: Stroke, t: f32) -> Stroke {
+    Stroke::new(lerp(a.width, b.width, t), lerp_color(a.color, b.color, t))
+}
+
+fn lerp_option<T: Copy>(a: Option<T>, b: Option<T>, t: f32, f: fn(T, T, f32) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(f(a, b, t)),
+        _ => b,
+    }
+}
+
+fn polyline_points(piecewise_segment: &PiecewiseSegment) -> Vec<Pos2> {
+    let mut points: Vec<Pos2> = piecewise_segment
+        .data
+        .first()
+        .map(|segment_data| segment_data.start)
+        .into_iter()
+        .collect();
+    points.extend(
+        piecewise_segment
+            .data
+            .iter()
+            .map(|segment_data| segment_data.end),
+    );
+    points
+}
+
+/// Resamples a polyline to `count` points evenly spaced by arc length.
+fn resample(points: &[Pos2], count: usize) -> Vec<Pos2> {
+    if points.len() == count || points.len() < 2 || count < 2 {
+        return points.to_vec();
+    }
+    let mut cumulative = vec![0.0];
+    for pair in points.windows(2) {
+        cumulative.push(cumulative[cumulative.len() - 1] + pair[0].distance(pair[1]));
+    }
+    let total = cumulative[cumulative.len() - 1];
+
+    let mut segment = 0;
+    (0..count)
+        .map(|i| {
+            let target = total * i as f32 / (count - 1) as f32;
+            while segment < points.len() - 2 && cumulative[segment + 1] < target {
+                segment += 1;
+            }
+            let length = cumulative[segment + 1] - cumulative[segment];
+            let t = if length > 0.0 {
+                (target - cumulative[segment]) / length
+            } else {
+                0.0
+            };
+            lerp_pos(points[segment], points[segment + 1], t)
+        })
+        .collect()
+}
+
+fn morph_rectangle(from: &Rectangle, to: &Rectangle, t: f32) -> Rectangle {
+    Rectangle {
+        x: lerp(from.x, to.x, t),
+        y: lerp(from.y, to.y, t),
+        width: lerp(from.width, to.width, t),
+        height: lerp(from.height, to.height, t),
+        fill_color: lerp_option(from.fill_color, to.fill_color, t, lerp_color),
+        stroke: lerp_option(from.stroke, to.stroke, t, lerp_stroke),
+        ..to.clone()
+    }
+}
+
+fn morph_circle(from: &Circle, to: &Circle, t: f32) -> Circle {
+    Circle {
+        center: lerp_pos(from.center, to.center, t),
+        radius: lerp(from.radius, to.radius, t),
+        fill_color: lerp_option(from.fill_color, to.fill_color, t, lerp_color),
+        stroke: lerp_option(from.stroke, to.stroke, t, lerp_stroke),
+        ..to.clone()
+    }
+}
+
+fn morph_segment(from: &Segment, to: &Segment, t: f32) -> Segment {
+    Segment {
+        data: SegmentData {
+            start: lerp_pos(from.data.start, to.data.start, t),
+            end: lerp_pos(from.data.end, to.data.end, t),
+        },
+        stroke: lerp_stroke(from.stroke, to.stroke, t),
+        ..to.clone()
+    }
+}
+
+fn morph_piecewise_segment(
+    from: &PiecewiseSegment,
+    to: &PiecewiseSegment,
+    t: f32,
+) -> Option<PiecewiseSegment> {
+    let from_points = polyline_points(from);
+    let to_points = polyline_points(to);
+    let count = from_points.len().max(to_points.len());
+    let points = resample(&from_points, count)
+        .into_iter()
+        .zip(resample(&to_points, count))
+        .map(|(a, b)| lerp_pos(a, b, t))
+        .collect();
+    let morphed = PiecewiseSegment::new(points)?;
+    Some(PiecewiseSegment {
+        data: morphed.data,
+        stroke: lerp_stroke(from.stroke, to.stroke, t),
+        ..to.clone()
+    })
+}
+
+fn morph_polygon(from: &Polygon, to: &Polygon, t: f32) -> Option<Polygon> {
+    if from.vertices.is_empty() || to.vertices.is_empty() {
+        return None;
+    }
+    // Resample the closed rings, then drop the repeated first vertex.
+    let ring = |vertices: &[Pos2]| -> Vec<Pos2> {
+        vertices.iter().chain(vertices.first()).copied().collect()
+    };
+    let count = from.vertices.len().max(to.vertices.len());
+    let mut vertices: Vec<Pos2> = resample(&ring(&from.vertices), count + 1)
+        .into_iter()
+        .zip(resample(&ring(&to.vertices), count + 1))
+        .map(|(a, b)| lerp_pos(a, b, t))
+        .collect();
+    vertices.pop();
+    Some(Polygon {
+        vertices,
+        fill_color: lerp_option(from.fill_color, to.fill_color, t, lerp_color),
+        stroke: lerp_option(from.stroke, to.stroke, t, lerp_stroke),
+        ..to.clone()
+    })
+}
+
+/// Interpolates between two contents of the same kind at `t` in `0.0..=1.0`.
+///
+/// Geometry, colors and stroke widths are interpolated; other attributes are
+/// taken from `to`. Polylines and polygons with different vertex counts are
+/// resampled by arc length. Returns `None` for contents of different or unsupported kinds.
+pub fn morph(from: &Content, to: &Content, t: f32) -> Option<Content> {
+    let t = t.clamp(0.0, 1.0);
+    match (from, to) {
+        (Content::Rectangle(from), Content::Rectangle(to)) => {
+            Some(morph_rectangle(from, to, t).into())
+        }
+        (Content::Circle(from), Content::Circle(to)) => Some(morph_circle(from, to, t).into()),
+        (Content::Segment(from), Content::Segment(to)) => Some(morph_segment(from, to, t).into()),
+        (Content::PiecewiseSegment(from), Content::PiecewiseSegment(to)) => {
+            morph_piecewise_segment(from, to, t).map(Content::from)
+        }
+        (Content::Polygon(from), Content::Polygon(to)) => {
+            morph_polygon(from, to, t).map(Content::from)
+        }
+        _ => None,
+    }
+}
+
+/// Timed transition between two contents with smoothstep easing.
+#[derive(Debug, Clone)]
+pub struct Morph {
+    pub from: Content,
+    pub to: Content,
+    /// Start time in seconds, on the same clock as `egui::InputState::time`
+    pub start_time: f64,
+    /// Duration in seconds
+    pub duration: f64,
+}
+
+impl Morph {
+    pub fn new(from: Content, to: Content, start_time: f64, duration: f64) -> Self {
+        Self {
+            from,
+            to,
+            start_time,
+            duration,
+        }
+    }
+
+    pub fn is_finished(&self, time: f64) -> bool {
+        time >= self.start_time + self.duration
+    }
+
+    /// The interpolated content at `time`. Request a repaint while
+    /// [`Self::is_finished`] is false to keep the transition running.
+    pub fn at(&self, time: f64) -> Option<Content> {
+        let t = if self.duration > 0.0 {
+            ((time - self.start_time) / self.duration).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        };
+        morph(&self.from, &self.to, t * t * (3.0 - 2.0 * t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Pos2, b: Pos2) {
+        assert!(a.distance(b) < 1e-4, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn resample_spaces_points_by_arc_length() {
+        let points = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 0.0),
+            Pos2::new(4.0, 0.0),
+        ];
+        let resampled = resample(&points, 5);
+        assert_eq!(resampled.len(), 5);
+        for (i, point) in resampled.into_iter().enumerate() {
+            assert_close(point, Pos2::new(i as f32, 0.0));
+        }
+    }
+
+    #[test]
+    fn resample_keeps_endpoints_when_reducing() {
+        let points: Vec<Pos2> = (0..10)
+            .map(|i| Pos2::new(i as f32, (i * i) as f32))
+            .collect();
+        let resampled = resample(&points, 3);
+        assert_eq!(resampled.len(), 3);
+        assert_close(resampled[0], points[0]);
+        assert_close(resampled[2], points[9]);
+    }
+
+    #[test]
+    fn resample_handles_zero_length_polylines() {
+        let points = [Pos2::new(1.0, 1.0); 3];
+        let resampled = resample(&points, 4);
+        assert_eq!(resampled.len(), 4);
+        assert!(resampled.iter().all(|&p| p == Pos2::new(1.0, 1.0)));
+    }
+
+    #[test]
+    fn resample_leaves_degenerate_input_alone() {
+        assert!(resample(&[], 4).is_empty());
+        assert_eq!(resample(&[Pos2::ZERO], 4), vec![Pos2::ZERO]);
+    }
+
+    #[test]
+    fn morph_piecewise_segment_with_different_vertex_counts() {
+        let from = PiecewiseSegment::new(vec![Pos2::new(0.0, 0.0), Pos2::new(2.0, 0.0)]).unwrap();
+        let to = PiecewiseSegment::new(vec![
+            Pos2::new(0.0, 2.0),
+            Pos2::new(1.0, 2.0),
+            Pos2::new(2.0, 2.0),
+        ])
+        .unwrap();
+        let Some(Content::PiecewiseSegment(morphed)) = morph(&from.into(), &to.into(), 0.5) else {
+            panic!("expected a piecewise segment");
+        };
+        let points = polyline_points(&morphed);
+        assert_eq!(points.len(), 3);
+        assert_close(points[0], Pos2::new(0.0, 1.0));
+        assert_close(points[1], Pos2::new(1.0, 1.0));
+        assert_close(points[2], Pos2::new(2.0, 1.0));
+    }
+
+    #[test]
+    fn morph_polygon_resamples_to_the_larger_vertex_count() {
+        let triangle = Polygon::new(vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(0.0, 3.0),
+        ]);
+        let square = Polygon::new(vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 0.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(0.0, 1.0),
+        ]);
+        let Some(Content::Polygon(morphed)) = morph(&triangle.into(), &square.clone().into(), 1.0)
+        else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(morphed.vertices.len(), 4);
+        for (a, b) in morphed.vertices.iter().zip(&square.vertices) {
+            assert_close(*a, *b);
+        }
+    }
+
+    #[test]
+    fn morph_polygon_without_vertices_is_none() {
+        let empty = Polygon::new(Vec::new());
+        let square = Polygon::new(vec![Pos2::ZERO, Pos2::new(1.0, 0.0), Pos2::new(1.0, 1.0)]);
+        assert!(morph(&empty.into(), &square.into(), 0.5).is_none());
+    }
+
+    #[test]
+    fn morph_of_different_kinds_is_none() {
+        let circle = Circle::new().with_radius(1.0);
+        let rectangle = Rectangle::new();
+        assert!(morph(&circle.into(), &rectangle.into(), 0.5).is_none());
+    }
+}
@@ -0,0 +1,240 @@
+use egui::emath::Rot2;
+use egui::{Color32, Mesh, Pos2, Stroke, Vec2};
+
+/// Shape of the outer corner where two thick line segments meet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corner, cut back to a bevel when it gets longer than four widths
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// Shape of the ends of an open line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends exactly at the end point
+    #[default]
+    Butt,
+    Round,
+    /// Extends past the end point by half the width
+    Square,
+}
+
+/// Join and cap of thick outlines.
+///
+/// The default (miter joins, butt caps) is painted by egui's anti-aliased path
+/// tessellator. Other styles are tessellated by this crate without feathering.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StrokeStyle {
+    pub join: LineJoin,
+    pub cap: LineCap,
+}
+
+impl StrokeStyle {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+const MITER_LIMIT: f32 = 4.0;
+const ROUND_STEP: f32 = std::f32::consts::PI / 8.0;
+
+fn triangle(mesh: &mut Mesh, a: Pos2, b: Pos2, c: Pos2, color: Color32) {
+    let index = mesh.vertices.len() as u32;
+    mesh.colored_vertex(a, color);
+    mesh.colored_vertex(b, color);
+    mesh.colored_vertex(c, color);
+    mesh.add_triangle(index, index + 1, index + 2);
+}
+
+/// Fan of triangles around `center`, sweeping `from` by `angle` radians.
+fn fan(mesh: &mut Mesh, center: Pos2, from: Vec2, angle: f32, color: Color32) {
+    let steps = (angle.abs() / ROUND_STEP).ceil().max(1.0) as usize;
+    let step = Rot2::from_angle(angle / steps as f32);
+    let mut offset = from;
+    for _ in 0..steps {
+        let next = step * offset;
+        triangle(mesh, center, center + offset, center + next, color);
+        offset = next;
+    }
+}
+
+fn cap(mesh: &mut Mesh, end: Pos2, outward: Vec2, half_width: f32, cap: LineCap, color: Color32) {
+    let normal = outward.rot90() * half_width;
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Round => {
+            // Sweep through `outward`, whichever way `rot90` turns.
+            let towards_outward = (normal.x * outward.y - normal.y * outward.x).signum();
+            fan(
+                mesh,
+                end,
+                normal,
+                towards_outward * std::f32::consts::PI,
+                color,
+            );
+        }
+        LineCap::Square => {
+            let tip = outward * half_width;
+            triangle(mesh, end + normal, end - normal, end - normal + tip, color);
+            triangle(
+                mesh,
+                end + normal,
+                end - normal + tip,
+                end + normal + tip,
+                color,
+            );
+        }
+    }
+}
+
+fn join(
+    mesh: &mut Mesh,
+    point: Pos2,
+    incoming: Vec2,
+    outgoing: Vec2,
+    half_width: f32,
+    style: LineJoin,
+    color: Color32,
+) {
+    let turn = incoming.x * outgoing.y - incoming.y * outgoing.x;
+    if turn.abs() < 1e-6 && incoming.dot(outgoing) > 0.0 {
+        return;
+    }
+    // The gap to fill is on the side facing away from the turn.
+    let side = if outgoing.dot(incoming.rot90()) > 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    let a = incoming.rot90() * side * half_width;
+    let b = outgoing.rot90() * side * half_width;
+    let angle = turn.atan2(incoming.dot(outgoing));
+    match style {
+        LineJoin::Bevel => triangle(mesh, point, point + a, point + b, color),
+        LineJoin::Round => fan(mesh, point, a, angle, color),
+        LineJoin::Miter => {
+            // A full reversal has no miter direction; bevel it like a sharp turn.
+            let sum = a + b;
+            if sum.length() <= half_width * 1e-4 {
+                triangle(mesh, point, point + a, point + b, color);
+                return;
+            }
+            let direction = sum.normalized();
+            let cos_half = direction.dot(a.normalized());
+            if cos_half <= 1.0 / MITER_LIMIT {
+                triangle(mesh, point, point + a, point + b, color);
+            } else {
+                let tip = point + direction * (half_width / cos_half);
+                triangle(mesh, point, point + a, tip, color);
+                triangle(mesh, point, tip, point + b, color);
+            }
+        }
+    }
+}
+
+/// A polyline point with its own stroke width and color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StrokeVertex {
+    pub pos: Pos2,
+    pub width: f32,
+    pub color: Color32,
+}
+
+/// Tessellates a thick polyline in screen coordinates.
+pub(crate) fn polyline_mesh(
+    points: &[Pos2],
+    closed: bool,
+    stroke: Stroke,
+    style: StrokeStyle,
+) -> Mesh {
+    let vertices: Vec<StrokeVertex> = points
+        .iter()
+        .map(|&pos| StrokeVertex {
+            pos,
+            width: stroke.width,
+            color: stroke.color,
+        })
+        .collect();
+    vertex_stroke_mesh(&vertices, closed, style)
+}
+
+/// Like [`polyline_mesh`] with a width and color per point, blended linearly
+/// along each segment.
+pub(crate) fn vertex_stroke_mesh(
+    vertices: &[StrokeVertex],
+    closed: bool,
+    style: StrokeStyle,
+) -> Mesh {
+    let mut mesh = Mesh::default();
+    let mut vertices = vertices.to_vec();
+    vertices.dedup_by_key(|vertex| vertex.pos);
+    if closed
+        && vertices.len() > 2
+        && vertices.first().map(|v| v.pos) == vertices.last().map(|v| v.pos)
+    {
+        vertices.pop();
+    }
+    if vertices.len() < 2 || vertices.iter().all(|vertex| vertex.width <= 0.0) {
+        return mesh;
+    }
+
+    let count = vertices.len();
+    let vertex = |i: usize| vertices[i % count];
+    let half_width = |i: usize| vertex(i).width.max(0.0) / 2.0;
+    let segment_count = if closed { count } else { count - 1 };
+    let direction = |i: usize| (vertex(i + 1).pos - vertex(i).pos).normalized();
+
+    for i in 0..segment_count {
+        let normal = direction(i).rot90();
+        let (start, end) = (vertex(i), vertex(i + 1));
+        let start_offset = normal * half_width(i);
+        let end_offset = normal * half_width(i + 1);
+        let index = mesh.vertices.len() as u32;
+        mesh.colored_vertex(start.pos + start_offset, start.color);
+        mesh.colored_vertex(start.pos - start_offset, start.color);
+        mesh.colored_vertex(end.pos - end_offset, end.color);
+        mesh.colored_vertex(end.pos + end_offset, end.color);
+        mesh.add_triangle(index, index + 1, index + 2);
+        mesh.add_triangle(index, index + 2, index + 3);
+    }
+
+    let joins = if closed { 0..count } else { 1..count - 1 };
+    for i in joins {
+        let incoming = direction((i + segment_count - 1) % segment_count);
+        let v = vertex(i);
+        join(
+            &mut mesh,
+            v.pos,
+            incoming,
+            direction(i),
+            half_width(i),
+            style.join,
+            v.color,
+        );
+    }
+
+    if !closed {
+        let last = count - 1;
+        let (first_vertex, last_vertex) = (vertex(0), vertex(last));
+        cap(
+            &mut mesh,
+            first_vertex.pos,
+            -direction(0),
+            half_width(0),
+            style.cap,
+            first_vertex.color,
+        );
+        cap(
+            &mut mesh,
+            last_vertex.pos,
+            direction(last - 1),
+            half_width(last),
+            style.cap,
+            last_vertex.color,
+        );
+    }
+    mesh
+}
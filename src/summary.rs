@@ -0,0 +1,59 @@
+use crate::{contents_bounding_rect, Content};
+use egui::Rect;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Non-visual summary of a set of contents, e.g. for assistive tech or logs.
+#[derive(Debug, Clone, Default)]
+pub struct SceneSummary {
+    /// Number of contents per kind
+    pub counts: BTreeMap<&'static str, usize>,
+    /// Labels in paint order, with the kind of the labeled content
+    pub labels: Vec<(&'static str, String)>,
+    /// Union of the known content extents in canvas coordinates
+    pub bounds: Option<Rect>,
+}
+
+pub fn describe(contents: &[Content]) -> SceneSummary {
+    let mut counts = BTreeMap::new();
+    let mut labels = Vec::new();
+    for content in crate::iter_ordered(contents) {
+        *counts.entry(content.kind_name()).or_insert(0) += 1;
+        if let Some(label) = content.label() {
+            labels.push((content.kind_name(), label.to_string()));
+        }
+    }
+
+    SceneSummary {
+        counts,
+        labels,
+        bounds: contents_bounding_rect(contents),
+    }
+}
+
+impl fmt::Display for SceneSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: usize = self.counts.values().sum();
+        write!(f, "{total} contents")?;
+        if !self.counts.is_empty() {
+            let counts = self
+                .counts
+                .iter()
+                .map(|(kind, count)| format!("{count} {kind}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, " ({counts})")?;
+        }
+        if let Some(bounds) = self.bounds {
+            write!(
+                f,
+                " spanning ({:.1}, {:.1}) to ({:.1}, {:.1})",
+                bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y
+            )?;
+        }
+        for (kind, label) in &self.labels {
+            write!(f, "\n{kind} labeled \"{label}\"")?;
+        }
+        Ok(())
+    }
+}